@@ -0,0 +1,303 @@
+//! # Cookie Module
+//!
+//! [`CookieBuilder`] serializes `Set-Cookie` values with their attributes, and
+//! the [`CookieJar`] behind [`Response::cookies_mut`](crate::Response::cookies_mut)
+//! holds every cookie a response sets. Incoming cookies are read with
+//! [`Request::cookie`](crate::Request::cookie).
+//!
+//! ## Example
+//! ```no_run
+//! use rxpress::Server;
+//! use rxpress::cookie::{CookieBuilder, SameSite};
+//!
+//! fn main() {
+//!     let mut app = Server::new("3000");
+//!
+//!     app.post("/login", |_req, res| {
+//!         let session = CookieBuilder::new("session", "abc123")
+//!             .path("/")
+//!             .max_age(3600)
+//!             .http_only()
+//!             .secure()
+//!             .same_site(SameSite::Strict);
+//!
+//!         res.set_cookie(&session).cookie("theme", "dark").send("Logged in");
+//!     });
+//!
+//!     app.run_or_panic();
+//! }
+//! ```
+
+use std::fmt;
+
+use crate::response::Response;
+
+/// The `SameSite` cookie attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSite {
+    /// Sent only with same-site requests
+    Strict,
+    /// Also sent on top-level navigations from other sites
+    Lax,
+    /// Sent with cross-site requests too (browsers require `Secure`)
+    None,
+}
+
+impl SameSite {
+    /// Returns the attribute value as written in the header.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SameSite::Strict => "Strict",
+            SameSite::Lax => "Lax",
+            SameSite::None => "None",
+        }
+    }
+}
+
+/// Builds a `Set-Cookie` value (`name=value; Path=/; HttpOnly`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CookieBuilder {
+    name: String,
+    value: String,
+    path: Option<String>,
+    max_age: Option<u64>,
+    http_only: bool,
+    secure: bool,
+    same_site: Option<SameSite>,
+}
+
+impl CookieBuilder {
+    /// Starts a cookie with no attributes.
+    pub fn new(name: &str, value: &str) -> CookieBuilder {
+        CookieBuilder {
+            name: name.to_string(),
+            value: value.to_string(),
+            path: None,
+            max_age: None,
+            http_only: false,
+            secure: false,
+            same_site: None,
+        }
+    }
+
+    /// Sets the `Path` the cookie is sent for.
+    pub fn path(mut self, path: &str) -> CookieBuilder {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    /// Sets `Max-Age` in seconds; `0` deletes the cookie.
+    pub fn max_age(mut self, seconds: u64) -> CookieBuilder {
+        self.max_age = Some(seconds);
+        self
+    }
+
+    /// Hides the cookie from scripts.
+    pub fn http_only(mut self) -> CookieBuilder {
+        self.http_only = true;
+        self
+    }
+
+    /// Only sends the cookie over HTTPS.
+    pub fn secure(mut self) -> CookieBuilder {
+        self.secure = true;
+        self
+    }
+
+    /// Sets the `SameSite` policy.
+    pub fn same_site(mut self, same_site: SameSite) -> CookieBuilder {
+        self.same_site = Some(same_site);
+        self
+    }
+
+    /// Returns the cookie name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the cookie value.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Serializes the cookie as a `Set-Cookie` value.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::cookie::{CookieBuilder, SameSite};
+    ///
+    /// let cookie = CookieBuilder::new("id", "7").path("/").http_only().same_site(SameSite::Lax);
+    /// assert_eq!(cookie.build(), "id=7; Path=/; HttpOnly; SameSite=Lax");
+    /// ```
+    pub fn build(&self) -> String {
+        let mut out = format!("{}={}", self.name, self.value);
+        if let Some(path) = &self.path {
+            out.push_str(&format!("; Path={}", path));
+        }
+        if let Some(max_age) = self.max_age {
+            out.push_str(&format!("; Max-Age={}", max_age));
+        }
+        if self.http_only {
+            out.push_str("; HttpOnly");
+        }
+        if self.secure {
+            out.push_str("; Secure");
+        }
+        if let Some(same_site) = self.same_site {
+            out.push_str(&format!("; SameSite={}", same_site.as_str()));
+        }
+        out
+    }
+}
+
+impl fmt::Display for CookieBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.build())
+    }
+}
+
+/// The cookies set on a [`Response`], written as one `Set-Cookie` line each.
+///
+/// # Example
+/// ```
+/// use rxpress::Response;
+/// use rxpress::cookie::CookieBuilder;
+///
+/// let mut out: Vec<u8> = Vec::new();
+/// let mut res = Response::new(&mut out);
+/// res.cookies_mut()
+///     .add(CookieBuilder::new("session", "abc").path("/").http_only())
+///     .remove("legacy");
+/// res.end();
+///
+/// let out = String::from_utf8(out).unwrap();
+/// assert!(out.contains("Set-Cookie: session=abc; Path=/; HttpOnly\r\n"));
+/// assert!(out.contains("Set-Cookie: legacy=; Max-Age=0\r\n"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CookieJar {
+    cookies: Vec<CookieBuilder>,
+}
+
+impl CookieJar {
+    /// Creates an empty jar.
+    pub fn new() -> CookieJar {
+        CookieJar::default()
+    }
+
+    /// Adds a cookie, replacing one added earlier with the same name and path.
+    ///
+    /// Cookie names must be valid tokens and values may not contain `;`, `,`,
+    /// quotes, backslashes, whitespace, or control characters; a `Path` may not
+    /// contain `;` or control characters. Invalid cookies print a warning and
+    /// are ignored.
+    pub fn add(&mut self, cookie: CookieBuilder) -> &mut Self {
+        let valid_value = cookie
+            .value
+            .chars()
+            .all(|c| c.is_ascii_graphic() && !matches!(c, ';' | ',' | '"' | '\\'));
+        // a CR/LF would end the header line, a `;` start a new attribute
+        let valid_path = cookie
+            .path
+            .as_deref()
+            .is_none_or(|path| !path.chars().any(|c| c.is_control() || c == ';'));
+        if !Response::is_token(&cookie.name) || !valid_value || !valid_path {
+            eprintln!(
+                "[rxpress warning!]: invalid cookie `{}`, ignoring CookieJar::add() call.",
+                cookie.name
+            );
+            return self;
+        }
+
+        match self
+            .cookies
+            .iter_mut()
+            .find(|c| c.name == cookie.name && c.path == cookie.path)
+        {
+            Some(existing) => *existing = cookie,
+            None => self.cookies.push(cookie),
+        }
+        self
+    }
+
+    /// Tells the client to delete `name` by sending it empty with `Max-Age=0`.
+    ///
+    /// Replaces a cookie of that name added earlier, keeping its `Path` so the
+    /// removal targets the same cookie.
+    pub fn remove(&mut self, name: &str) -> &mut Self {
+        let path = self
+            .cookies
+            .iter()
+            .find(|c| c.name == name)
+            .and_then(|c| c.path.clone());
+        let mut cookie = CookieBuilder::new(name, "").max_age(0);
+        cookie.path = path;
+        self.add(cookie)
+    }
+
+    /// Gets a cookie added to the jar by name.
+    pub fn get(&self, name: &str) -> Option<&CookieBuilder> {
+        self.cookies.iter().find(|c| c.name == name)
+    }
+
+    /// Iterates over the cookies in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &CookieBuilder> {
+        self.cookies.iter()
+    }
+
+    /// Returns the number of cookies in the jar.
+    pub fn len(&self) -> usize {
+        self.cookies.len()
+    }
+
+    /// Returns `true` if no cookies are set.
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // TEST - every attribute is serialized in a fixed order
+    #[test]
+    fn test_build_all_attributes() {
+        let cookie = CookieBuilder::new("session", "abc")
+            .same_site(SameSite::None)
+            .secure()
+            .http_only()
+            .max_age(0)
+            .path("/app");
+        assert_eq!(
+            cookie.build(),
+            "session=abc; Path=/app; Max-Age=0; HttpOnly; Secure; SameSite=None"
+        );
+        assert_eq!(CookieBuilder::new("a", "").to_string(), "a=");
+    }
+
+    // TEST - two cookies added and a third removed give three entries
+    #[test]
+    fn test_jar_add_and_remove() {
+        let mut jar = CookieJar::new();
+        jar.add(CookieBuilder::new("session", "abc").path("/").secure())
+            .add(CookieBuilder::new("theme", "dark").max_age(86400))
+            .add(CookieBuilder::new("tracking", "1").path("/ads"))
+            .add(CookieBuilder::new("theme", "light").max_age(60)) // replaces
+            .add(CookieBuilder::new("bad", "a b")) // ignored
+            .add(CookieBuilder::new("theme", "x").path("/\r\nEvil: 2")) // ignored
+            .add(CookieBuilder::new("theme", "x").path("/; Secure")) // ignored
+            .remove("tracking");
+
+        let lines: Vec<String> = jar.iter().map(|c| c.build()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "session=abc; Path=/; Secure",
+                "theme=light; Max-Age=60",
+                "tracking=; Path=/ads; Max-Age=0",
+            ]
+        );
+        assert_eq!(jar.get("theme").map(|c| c.value()), Some("light"));
+    }
+}
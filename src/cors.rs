@@ -0,0 +1,194 @@
+//! # CORS Module
+//!
+//! Cross-Origin Resource Sharing support. Configure a [`Cors`] policy and install
+//! it with [`Server::cors`](crate::Server::cors); the matching
+//! `Access-Control-*` headers are then added to every response, and preflight
+//! requests are answered with the methods registered for the requested path.
+//!
+//! ## Example
+//! ```no_run
+//! use rxpress::Server;
+//! use rxpress::cors::{AllowOrigin, Cors};
+//!
+//! fn main() {
+//!     let mut app = Server::new("3000");
+//!
+//!     // only this origin may make credentialed requests
+//!     let origin = AllowOrigin::Exact("https://app.example.com".into());
+//!     app.cors(Cors::new(origin).allow_credentials(true));
+//!
+//!     app.get("/", |_req, res| {
+//!         res.send("Hello from rxpress!");
+//!     });
+//!
+//!     app.run_or_panic();
+//! }
+//! ```
+
+use crate::request::Request;
+use crate::response::Response;
+
+/// Which origins are allowed to access the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllowOrigin {
+    /// Any origin (`Access-Control-Allow-Origin: *`)
+    Any,
+    /// A single fixed origin, e.g. `https://example.com`
+    Exact(String),
+    /// Reflect the request's `Origin` header back, adding `Vary: Origin`.
+    ///
+    /// Meant for development: every site is allowed, and together with
+    /// [`Cors::allow_credentials`] any site can make credentialed requests,
+    /// which browsers refuse for `*`.
+    Mirror,
+}
+
+/// A CORS policy applied to every response.
+#[derive(Debug, Clone)]
+pub struct Cors {
+    allow_origin: AllowOrigin,
+    allow_credentials: bool,
+}
+
+impl Cors {
+    /// Creates a new [`Cors`] policy for the given origins.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::cors::{AllowOrigin, Cors};
+    ///
+    /// let cors = Cors::new(AllowOrigin::Exact("https://example.com".into()));
+    /// ```
+    pub fn new(allow_origin: AllowOrigin) -> Cors {
+        Cors {
+            allow_origin,
+            allow_credentials: false,
+        }
+    }
+
+    /// Sends `Access-Control-Allow-Credentials: true` when enabled.
+    pub fn allow_credentials(mut self, enabled: bool) -> Cors {
+        self.allow_credentials = enabled;
+        self
+    }
+
+    /// Adds the CORS headers for `req` to `res`.
+    pub(crate) fn apply(&self, req: &Request, res: &mut Response) {
+        match &self.allow_origin {
+            AllowOrigin::Any => {
+                res.set_header("Access-Control-Allow-Origin", "*");
+            }
+            AllowOrigin::Exact(origin) => {
+                res.set_header("Access-Control-Allow-Origin", origin);
+            }
+            AllowOrigin::Mirror => {
+                if let Some(origin) = req.header("Origin") {
+                    res.set_header("Access-Control-Allow-Origin", origin);
+                }
+                res.set_header("Vary", "Origin");
+            }
+        }
+
+        if self.allow_credentials {
+            res.set_header("Access-Control-Allow-Credentials", "true");
+        }
+    }
+
+    /// Answers a preflight request (`OPTIONS` with `Access-Control-Request-Method`)
+    /// with `204 No Content`, allowing `methods`.
+    ///
+    /// Returns `false` without sending anything if `req` isn't a preflight, no
+    /// route matches, or the path has its own `OPTIONS` handler.
+    pub(crate) fn preflight(&self, req: &Request, res: &mut Response, methods: &[&str]) -> bool {
+        let is_preflight = req.method.eq_ignore_ascii_case("OPTIONS")
+            && req.header("Access-Control-Request-Method").is_some();
+        if !is_preflight || methods.is_empty() || methods.contains(&"OPTIONS") {
+            return false;
+        }
+
+        res.set_header("Access-Control-Allow-Methods", &methods.join(", "));
+        if let Some(headers) = req.header("Access-Control-Request-Headers") {
+            res.set_header("Access-Control-Allow-Headers", headers);
+        }
+        res.status(204).end();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::Read;
+    use std::net::{TcpListener, TcpStream};
+
+    // helper to apply a policy and read the raw response
+    fn apply_and_read(cors: &Cors, req: &Request) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let mut stream = listener.accept().unwrap().0;
+
+        let mut res = Response::new(&mut stream);
+        cors.apply(req, &mut res);
+        res.send("ok");
+        drop(stream);
+
+        let mut out = String::new();
+        client.read_to_string(&mut out).unwrap();
+        out
+    }
+
+    // TEST - mirror mode echoes the Origin and sets Vary
+    #[test]
+    fn test_mirror_origin() {
+        let mut headers = HashMap::new();
+        headers.insert("origin".to_string(), "https://app.example.com".to_string());
+        let req = Request::new("GET / HTTP/1.1", headers, "".into());
+
+        let cors = Cors::new(AllowOrigin::Mirror).allow_credentials(true);
+        let out = apply_and_read(&cors, &req);
+        assert!(out.contains("Access-Control-Allow-Origin: https://app.example.com\r\n"));
+        assert!(out.contains("Vary: Origin\r\n"));
+        assert!(out.contains("Access-Control-Allow-Credentials: true\r\n"));
+    }
+
+    // TEST - preflights list the given methods
+    #[test]
+    fn test_preflight() {
+        let mut headers = HashMap::new();
+        headers.insert(
+            "access-control-request-method".to_string(),
+            "POST".to_string(),
+        );
+        let req = Request::new("OPTIONS /b HTTP/1.1", headers, "".into());
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let mut stream = listener.accept().unwrap().0;
+
+        let cors = Cors::new(AllowOrigin::Any);
+        let mut res = Response::new(&mut stream);
+        assert!(!cors.preflight(&req, &mut res, &[]));
+        assert!(cors.preflight(&req, &mut res, &["GET", "POST"]));
+        drop(stream);
+
+        let mut out = String::new();
+        client.read_to_string(&mut out).unwrap();
+        assert!(out.starts_with("HTTP/1.1 204 No Content\r\n"));
+        assert!(out.contains("Access-Control-Allow-Methods: GET, POST\r\n"));
+    }
+
+    // TEST - wildcard and exact origins
+    #[test]
+    fn test_any_and_exact_origin() {
+        let req = Request::new("GET / HTTP/1.1", HashMap::new(), "".into());
+
+        let out = apply_and_read(&Cors::new(AllowOrigin::Any), &req);
+        assert!(out.contains("Access-Control-Allow-Origin: *\r\n"));
+        assert!(!out.contains("Vary"));
+
+        let exact = Cors::new(AllowOrigin::Exact("https://example.com".into()));
+        let out = apply_and_read(&exact, &req);
+        assert!(out.contains("Access-Control-Allow-Origin: https://example.com\r\n"));
+    }
+}
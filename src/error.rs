@@ -0,0 +1,119 @@
+//! # Error Module
+//!
+//! The [`RxError`] type lets fallible handlers (see
+//! [`Server::try_route`](crate::Server::try_route)) bail out with `?`.
+//! Any [`std::error::Error`] converts into a `500 Internal Server Error`.
+//!
+//! ## Example
+//! ```no_run
+//! use rxpress::{RxError, Server};
+//!
+//! fn main() {
+//!     let mut app = Server::new("3000");
+//!
+//!     app.try_route("GET", "/config", |_req, res| {
+//!         let config = std::fs::read_to_string("config.toml")?; // io::Error -> 500
+//!         res.send(&config);
+//!         Ok(())
+//!     });
+//!
+//!     app.try_route("GET", "/items/:id", |req, res| {
+//!         let id = req.param_or("id", "");
+//!         if id.is_empty() {
+//!             return Err(RxError::new(400, "missing id"));
+//!         }
+//!         res.send(id);
+//!         Ok(())
+//!     });
+//!
+//!     app.run_or_panic();
+//! }
+//! ```
+
+use std::fmt;
+
+/// An error returned from a fallible handler, turned into an HTTP error response.
+///
+/// The message is only sent to the client when
+/// [`Server::debug_errors`](crate::Server::debug_errors) is enabled; otherwise the
+/// standard reason phrase is used.
+pub struct RxError {
+    status: u16,
+    message: String,
+}
+
+impl RxError {
+    /// Creates a new [`RxError`] with a status code and message.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::RxError;
+    ///
+    /// let err = RxError::new(404, "user not found");
+    /// assert_eq!(err.status(), 404);
+    /// assert_eq!(err.message(), "user not found");
+    /// ```
+    pub fn new(status: u16, message: impl Into<String>) -> RxError {
+        RxError {
+            status,
+            message: message.into(),
+        }
+    }
+
+    /// The HTTP status code of the error response.
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    /// The error message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// Any standard error becomes a `500` carrying the error's `Display` output.
+///
+/// # Example
+/// ```
+/// use rxpress::RxError;
+///
+/// fn parse(id: &str) -> Result<u32, RxError> {
+///     Ok(id.parse::<u32>()?)
+/// }
+///
+/// assert_eq!(parse("abc").unwrap_err().status(), 500);
+/// ```
+impl<E: std::error::Error> From<E> for RxError {
+    fn from(err: E) -> RxError {
+        RxError::new(500, err.to_string())
+    }
+}
+
+impl fmt::Debug for RxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RxError")
+            .field("status", &self.status)
+            .field("message", &self.message)
+            .finish()
+    }
+}
+
+impl fmt::Display for RxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.status, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // TEST - std errors convert into a 500
+    #[test]
+    fn test_from_std_error() {
+        let err: RxError = "x".parse::<u8>().unwrap_err().into();
+        assert_eq!(err.status(), 500);
+        assert_eq!(err.message(), "invalid digit found in string");
+        assert_eq!(err.to_string(), "500 invalid digit found in string");
+    }
+}
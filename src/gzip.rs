@@ -0,0 +1,253 @@
+//! # Gzip Module
+//!
+//! A small, dependency-free gzip encoder behind the `gzip` feature, used by
+//! [`Server::enable_compression`](crate::Server::enable_compression) and handy
+//! for pre-compressing assets.
+//!
+//! Bodies are deflated (RFC 1951) with LZ77 matching over a 32 KiB window and
+//! the fixed Huffman codes, trading a little ratio for a compact encoder.
+//!
+//! ## Example
+//! ```
+//! # #[cfg(feature = "gzip")]
+//! # {
+//! use rxpress::gzip;
+//!
+//! let body = "hello rxpress! ".repeat(100);
+//! let packed = gzip::compress(body.as_bytes());
+//! assert_eq!(&packed[..2], &[0x1f, 0x8b]);
+//! assert!(packed.len() < body.len() / 10);
+//! # }
+//! ```
+
+/// Member header: magic, deflate, no flags, no mtime, unknown OS.
+const HEADER: [u8; 10] = [0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];
+
+const WINDOW: usize = 32 * 1024;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+/// Candidates tried per position before settling for the best match so far
+const MAX_CHAIN: usize = 64;
+const HASH_BITS: u32 = 15;
+const NONE: usize = usize::MAX;
+
+const LEN_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LEN_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Compresses `data` into a complete gzip member.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut out = BitWriter {
+        bytes: HEADER.to_vec(),
+        acc: 0,
+        len: 0,
+    };
+
+    // a single final block with fixed Huffman codes
+    out.write_bits(1, 1);
+    out.write_bits(1, 2);
+
+    let mut head = vec![NONE; 1 << HASH_BITS];
+    let mut prev = vec![NONE; WINDOW];
+    let mut i = 0;
+    while i < data.len() {
+        let (len, dist) = longest_match(data, i, &head, &prev);
+        if len >= MIN_MATCH {
+            out.write_length(len);
+            out.write_distance(dist);
+        } else {
+            out.write_symbol(data[i] as u16);
+        }
+        for pos in i..i + len.max(1) {
+            if pos + MIN_MATCH <= data.len() {
+                let h = hash(data, pos);
+                prev[pos % WINDOW] = head[h];
+                head[h] = pos;
+            }
+        }
+        i += len.max(1);
+    }
+    out.write_symbol(256); // end of block
+    out.flush();
+
+    let mut bytes = out.bytes;
+    bytes.extend_from_slice(&crc32(data).to_le_bytes());
+    bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    bytes
+}
+
+/// Checks whether a body of `content_type` is worth compressing: text and
+/// structured formats are, images, archives, and other binaries are not.
+pub(crate) fn is_compressible(content_type: &str) -> bool {
+    let media = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    media.starts_with("text/")
+        || ["json", "javascript", "xml", "svg", "wasm"]
+            .iter()
+            .any(|kind| media.contains(kind))
+}
+
+/*---- Private Functions ----*/
+/// Packs bits least-significant first, as DEFLATE requires.
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u64,
+    len: u32,
+}
+
+impl BitWriter {
+    fn write_bits(&mut self, value: u32, count: u32) {
+        self.acc |= (value as u64) << self.len;
+        self.len += count;
+        while self.len >= 8 {
+            self.bytes.push(self.acc as u8);
+            self.acc >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    /// Huffman codes are stored most-significant bit first.
+    fn write_code(&mut self, code: u32, count: u32) {
+        let reversed = code.reverse_bits() >> (32 - count);
+        self.write_bits(reversed, count);
+    }
+
+    /// Writes a literal/length symbol with its fixed code.
+    fn write_symbol(&mut self, symbol: u16) {
+        let (code, count) = match symbol {
+            0..=143 => (0x30 + symbol, 8),
+            144..=255 => (0x190 + symbol - 144, 9),
+            256..=279 => (symbol - 256, 7),
+            _ => (0xc0 + symbol - 280, 8),
+        };
+        self.write_code(code as u32, count);
+    }
+
+    fn write_length(&mut self, len: usize) {
+        let k = LEN_BASE
+            .iter()
+            .rposition(|&b| b as usize <= len)
+            .unwrap_or(0);
+        self.write_symbol(257 + k as u16);
+        self.write_bits((len - LEN_BASE[k] as usize) as u32, LEN_EXTRA[k] as u32);
+    }
+
+    fn write_distance(&mut self, dist: usize) {
+        let k = DIST_BASE
+            .iter()
+            .rposition(|&b| b as usize <= dist)
+            .unwrap_or(0);
+        self.write_code(k as u32, 5);
+        self.write_bits((dist - DIST_BASE[k] as usize) as u32, DIST_EXTRA[k] as u32);
+    }
+
+    /// Pads the last partial byte with zeros.
+    fn flush(&mut self) {
+        if self.len > 0 {
+            self.bytes.push(self.acc as u8);
+            self.acc = 0;
+            self.len = 0;
+        }
+    }
+}
+
+/// Hashes the three bytes starting at `pos`.
+fn hash(data: &[u8], pos: usize) -> usize {
+    let key = (data[pos] as u32) << 16 | (data[pos + 1] as u32) << 8 | data[pos + 2] as u32;
+    (key.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+}
+
+/// Finds the longest earlier match for the bytes at `pos`, as `(length, distance)`;
+/// the length is `0` when there is none.
+fn longest_match(data: &[u8], pos: usize, head: &[usize], prev: &[usize]) -> (usize, usize) {
+    if pos + MIN_MATCH > data.len() {
+        return (0, 0);
+    }
+    let max = (data.len() - pos).min(MAX_MATCH);
+    let mut best = (0, 0);
+    let mut candidate = head[hash(data, pos)];
+
+    for _ in 0..MAX_CHAIN {
+        if candidate == NONE || pos - candidate > WINDOW {
+            break;
+        }
+        let len = data[candidate..]
+            .iter()
+            .zip(&data[pos..pos + max])
+            .take_while(|(a, b)| a == b)
+            .count();
+        if len > best.0 {
+            best = (len, pos - candidate);
+            if len == max {
+                break;
+            }
+        }
+        // older entries in the ring may have been overwritten by newer ones
+        let next = prev[candidate % WINDOW];
+        if next == NONE || next >= candidate {
+            break;
+        }
+        candidate = next;
+    }
+    best
+}
+
+/// CRC-32 (IEEE) as used in the gzip trailer.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // TEST - an empty body is a minimal valid member
+    #[test]
+    fn test_compress_empty() {
+        let mut expected = HEADER.to_vec();
+        expected.extend_from_slice(&[0x03, 0x00, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(compress(b""), expected);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    // TEST - repetitive bodies shrink and the trailer records CRC and size
+    #[test]
+    fn test_compress_trailer() {
+        let body = r#"{"id":1,"name":"rxpress"},"#.repeat(200);
+        let packed = compress(body.as_bytes());
+        assert!(packed.len() < body.len() / 20);
+
+        let trailer = &packed[packed.len() - 8..];
+        assert_eq!(trailer[..4], crc32(body.as_bytes()).to_le_bytes());
+        assert_eq!(trailer[4..], (body.len() as u32).to_le_bytes());
+        assert!(is_compressible("application/json; charset=utf-8"));
+        assert!(!is_compressible("image/png"));
+    }
+}
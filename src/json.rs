@@ -0,0 +1,577 @@
+//! # JSON Module
+//!
+//! A minimal, dependency-free JSON value used by the request body helpers.
+//! The [`Json`] enum can parse any well-formed JSON document and serialize back
+//! to compact JSON through its [`Display`](std::fmt::Display) implementation.
+//!
+//! ## Example
+//! ```
+//! use rxpress::Json;
+//!
+//! let value = Json::parse(r#"{"name":"rxpress","tags":["http","server"]}"#).unwrap();
+//! assert_eq!(value.to_string(), r#"{"name":"rxpress","tags":["http","server"]}"#);
+//! assert!(Json::parse("{not json}").is_err());
+//! ```
+
+use std::fmt;
+
+/// Deepest nesting of arrays and objects [`Json::parse`] accepts, so hostile
+/// bodies can't exhaust the stack.
+pub const MAX_DEPTH: usize = 128;
+
+/// A parsed JSON value.
+///
+/// Objects keep their keys in document order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool(bool),
+    /// Any JSON number
+    Number(f64),
+    /// A JSON string
+    String(String),
+    /// A JSON array
+    Array(Vec<Json>),
+    /// A JSON object as ordered key-value pairs
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parses a JSON document. Arrays and objects nested deeper than
+    /// [`MAX_DEPTH`] are rejected.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Json;
+    ///
+    /// assert_eq!(Json::parse("[1, true, null]").unwrap().to_string(), "[1,true,null]");
+    /// assert!(Json::parse(r#"{"a":}"#).is_err());
+    /// ```
+    pub fn parse(input: &str) -> Result<Json, String> {
+        let mut parser = Parser {
+            bytes: input.as_bytes(),
+            pos: 0,
+            depth: 0,
+        };
+
+        parser.skip_whitespace();
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+
+        if parser.pos != parser.bytes.len() {
+            return Err(parser.error("unexpected trailing characters"));
+        }
+
+        Ok(value)
+    }
+
+    /// Gets an object member by key; `None` for a missing key or a non-object.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Json;
+    ///
+    /// let user = Json::parse(r#"{"name":"ana","age":30}"#).unwrap();
+    /// assert_eq!(user.get("name"), Some(&Json::String("ana".into())));
+    /// assert_eq!(user.get("email"), None);
+    /// ```
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Checks that this is an object with every key in `keys`, naming the
+    /// missing ones otherwise. A member set to `null` counts as present.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.post("/users", |req, res| {
+    ///     let user = match req.json() {
+    ///         Ok(user) => user,
+    ///         Err(err) => return res.status(400).send(&err),
+    ///     };
+    ///     match user.require(&["name", "email"]) {
+    ///         Ok(()) => res.status(201).json(&user.to_string()),
+    ///         Err(err) => res.status(422).send(&err),
+    ///     }
+    /// });
+    /// ```
+    /// ---
+    /// ## Test
+    /// ```
+    /// use rxpress::Json;
+    ///
+    /// let user = Json::parse(r#"{"name":"ana","email":"a@b.c"}"#).unwrap();
+    /// assert!(user.require(&["name", "email"]).is_ok());
+    /// ```
+    pub fn require(&self, keys: &[&str]) -> Result<(), String> {
+        if !matches!(self, Json::Object(_)) {
+            return Err("[rxpress error]: Expected a JSON object.".to_string());
+        }
+
+        let missing: Vec<String> = keys
+            .iter()
+            .filter(|key| self.get(key).is_none())
+            .map(|key| format!("`{}`", key))
+            .collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "[rxpress error]: Missing required field(s): {}.",
+                missing.join(", ")
+            ))
+        }
+    }
+
+    /// Applies an RFC 7386 JSON Merge Patch to this value.
+    ///
+    /// Object members in `patch` replace or add members, `null` members remove
+    /// them, and any non-object patch replaces the value entirely.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Json;
+    ///
+    /// let mut doc = Json::parse(r#"{"title":"Hi","tags":["a"]}"#).unwrap();
+    /// doc.merge_patch(&Json::parse(r#"{"title":"Hello","tags":null}"#).unwrap());
+    /// assert_eq!(doc.to_string(), r#"{"title":"Hello"}"#);
+    /// ```
+    pub fn merge_patch(&mut self, patch: &Json) {
+        let members = match patch {
+            Json::Object(members) => members,
+            other => {
+                *self = other.clone();
+                return;
+            }
+        };
+
+        if !matches!(self, Json::Object(_)) {
+            *self = Json::Object(Vec::new());
+        }
+        let Json::Object(target) = self else {
+            return;
+        };
+
+        for (key, value) in members {
+            let existing = target.iter().position(|(k, _)| k == key);
+            match (value, existing) {
+                (Json::Null, Some(i)) => {
+                    target.remove(i);
+                }
+                (Json::Null, None) => {}
+                (value, Some(i)) => target[i].1.merge_patch(value),
+                (value, None) => {
+                    let mut member = Json::Null;
+                    member.merge_patch(value);
+                    target.push((key.clone(), member));
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) => {
+                if n.is_finite() && n.fract() == 0.0 && n.abs() < 1e15 {
+                    write!(f, "{}", *n as i64)
+                } else {
+                    write!(f, "{}", n)
+                }
+            }
+            Json::String(s) => write_escaped(f, s),
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(pairs) => {
+                write!(f, "{{")?;
+                for (i, (key, val)) in pairs.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_escaped(f, key)?;
+                    write!(f, ":{}", val)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/*---- Private Functions ----*/
+/// Writes `s` as a quoted JSON string.
+fn write_escaped(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// Recursive-descent JSON parser over raw bytes.
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    /// Arrays and objects currently open
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, msg: &str) -> String {
+        format!(
+            "[rxpress error]: invalid JSON at byte {}: {}.",
+            self.pos, msg
+        )
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect_literal(&mut self, literal: &str, value: Json) -> Result<Json, String> {
+        if self.bytes[self.pos..].starts_with(literal.as_bytes()) {
+            self.pos += literal.len();
+            Ok(value)
+        } else {
+            Err(self.error("unexpected token"))
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Json, String> {
+        match self.peek() {
+            Some(b'n') => self.expect_literal("null", Json::Null),
+            Some(b't') => self.expect_literal("true", Json::Bool(true)),
+            Some(b'f') => self.expect_literal("false", Json::Bool(false)),
+            Some(b'"') => self.parse_string().map(Json::String),
+            Some(b'[' | b'{') if self.depth >= MAX_DEPTH => {
+                Err(self.error("exceeds maximum nesting depth"))
+            }
+            Some(b'[') => {
+                self.depth += 1;
+                let array = self.parse_array();
+                self.depth -= 1;
+                array
+            }
+            Some(b'{') => {
+                self.depth += 1;
+                let object = self.parse_object();
+                self.depth -= 1;
+                object
+            }
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            Some(_) => Err(self.error("unexpected token")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Json, String> {
+        self.pos += 1; // '['
+        let mut items = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Json::Array(items));
+        }
+
+        loop {
+            self.skip_whitespace();
+            items.push(self.parse_value()?);
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Json, String> {
+        self.pos += 1; // '{'
+        let mut pairs = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Json::Object(pairs));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error("expected string key"));
+            }
+            let key = self.parse_string()?;
+
+            self.skip_whitespace();
+            if self.peek() != Some(b':') {
+                return Err(self.error("expected `:`"));
+            }
+            self.pos += 1;
+
+            self.skip_whitespace();
+            let value = self.parse_value()?;
+            pairs.push((key, value));
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Json::Object(pairs));
+                }
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Json, String> {
+        let start = self.pos;
+
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+
+        match self.peek() {
+            Some(b'0') => self.pos += 1,
+            Some(b'1'..=b'9') => self.skip_digits(),
+            _ => return Err(self.error("invalid number")),
+        }
+
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            if !matches!(self.peek(), Some(b'0'..=b'9')) {
+                return Err(self.error("invalid number"));
+            }
+            self.skip_digits();
+        }
+
+        if let Some(b'e' | b'E') = self.peek() {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.pos += 1;
+            }
+            if !matches!(self.peek(), Some(b'0'..=b'9')) {
+                return Err(self.error("invalid number"));
+            }
+            self.skip_digits();
+        }
+
+        // the slice only contains ASCII digits, signs, '.', and exponents
+        let text = std::str::from_utf8(&self.bytes[start..self.pos]).unwrap();
+        text.parse::<f64>()
+            .map(Json::Number)
+            .map_err(|_| self.error("invalid number"))
+    }
+
+    fn skip_digits(&mut self) {
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.pos += 1; // opening quote
+        let mut out = String::new();
+
+        loop {
+            let start = self.pos;
+            while let Some(b) = self.peek() {
+                if b == b'"' || b == b'\\' || b < 0x20 {
+                    break;
+                }
+                self.pos += 1;
+            }
+            // input came from a &str and we only stop on ASCII bytes
+            out.push_str(std::str::from_utf8(&self.bytes[start..self.pos]).unwrap());
+
+            match self.peek() {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    self.parse_escape(&mut out)?;
+                }
+                Some(_) => return Err(self.error("control character in string")),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn parse_escape(&mut self, out: &mut String) -> Result<(), String> {
+        let c = match self.peek() {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\u{8}',
+            Some(b'f') => '\u{c}',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => {
+                self.pos += 1;
+                let high = self.parse_hex4()?;
+                let code = if (0xD800..0xDC00).contains(&high) {
+                    // surrogate pair: expect a second `\uXXXX`
+                    if !self.bytes[self.pos..].starts_with(b"\\u") {
+                        return Err(self.error("unpaired surrogate"));
+                    }
+                    self.pos += 2;
+                    let low = self.parse_hex4()?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return Err(self.error("unpaired surrogate"));
+                    }
+                    0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                } else {
+                    high
+                };
+                out.push(char::from_u32(code).ok_or_else(|| self.error("invalid escape"))?);
+                return Ok(());
+            }
+            _ => return Err(self.error("invalid escape")),
+        };
+
+        self.pos += 1;
+        out.push(c);
+        Ok(())
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, String> {
+        let hex = self
+            .bytes
+            .get(self.pos..self.pos + 4)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u32::from_str_radix(h, 16).ok())
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        self.pos += 4;
+        Ok(hex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // TEST - parse scalars and containers
+    #[test]
+    fn test_parse_values() {
+        assert_eq!(Json::parse("null").unwrap(), Json::Null);
+        assert_eq!(Json::parse(" true ").unwrap(), Json::Bool(true));
+        assert_eq!(Json::parse("-1.5e2").unwrap(), Json::Number(-150.0));
+        assert_eq!(
+            Json::parse(r#""a\"bé""#).unwrap(),
+            Json::String("a\"bé".to_string())
+        );
+        assert_eq!(
+            Json::parse(r#"{"a":[1,2],"b":{}}"#).unwrap(),
+            Json::Object(vec![
+                (
+                    "a".to_string(),
+                    Json::Array(vec![Json::Number(1.0), Json::Number(2.0)])
+                ),
+                ("b".to_string(), Json::Object(vec![])),
+            ])
+        );
+    }
+
+    // TEST - malformed documents are rejected
+    #[test]
+    fn test_parse_errors() {
+        assert!(Json::parse("").is_err());
+        assert!(Json::parse("{").is_err());
+        assert!(Json::parse(r#"{"a" 1}"#).is_err());
+        assert!(Json::parse("[1,]").is_err());
+        assert!(Json::parse("01").is_err());
+        assert!(Json::parse("\"unterminated").is_err());
+        assert!(Json::parse("true false").is_err());
+    }
+
+    // TEST - deeply nested documents fail instead of overflowing the stack
+    #[test]
+    fn test_parse_depth_limit() {
+        let nested = |depth| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(Json::parse(&nested(MAX_DEPTH)).is_ok());
+
+        let err = Json::parse(&nested(MAX_DEPTH + 1)).unwrap_err();
+        assert!(err.contains("exceeds maximum nesting depth"));
+        assert!(Json::parse(&"[{\"a\":".repeat(250_000)).is_err());
+    }
+
+    // TEST - required fields are reported by name
+    #[test]
+    fn test_require() {
+        let body = Json::parse(r#"{"name":"ana","nick":null,"tags":[]}"#).unwrap();
+        assert!(body.require(&["name", "nick", "tags"]).is_ok());
+        assert!(body.require(&[]).is_ok());
+        assert_eq!(
+            body.require(&["name", "email", "age"]).unwrap_err(),
+            "[rxpress error]: Missing required field(s): `email`, `age`."
+        );
+        assert!(Json::parse("[1]").unwrap().require(&["name"]).is_err());
+        assert_eq!(body.get("tags"), Some(&Json::Array(vec![])));
+        assert_eq!(Json::Null.get("name"), None);
+    }
+
+    // TEST - RFC 7386 merge patch
+    #[test]
+    fn test_merge_patch() {
+        let mut doc = Json::parse(r#"{"a":1,"b":1,"c":3}"#).unwrap();
+        doc.merge_patch(&Json::parse(r#"{"a":null,"b":2}"#).unwrap());
+        assert_eq!(doc.to_string(), r#"{"b":2,"c":3}"#);
+
+        // nested objects merge, nulls inside new members are dropped
+        let mut doc = Json::parse(r#"{"a":{"b":1},"x":[1]}"#).unwrap();
+        doc.merge_patch(&Json::parse(r#"{"a":{"c":2},"x":"s","n":{"d":null}}"#).unwrap());
+        assert_eq!(doc.to_string(), r#"{"a":{"b":1,"c":2},"x":"s","n":{}}"#);
+
+        // non-object patches replace the whole document
+        doc.merge_patch(&Json::Array(vec![]));
+        assert_eq!(doc, Json::Array(vec![]));
+    }
+
+    // TEST - serialization round trip
+    #[test]
+    fn test_display_round_trip() {
+        let input = r#"{"msg":"line\nbreak \"quoted\"","n":3,"f":0.5,"list":[null,false]}"#;
+        assert_eq!(Json::parse(input).unwrap().to_string(), input);
+    }
+}
@@ -26,16 +26,25 @@
 //!         res.status(201).json(r#"{"status":"created"}"#);
 //!     });
 //!
-//!     app.run(); // blocks forever
+//!     app.run_or_panic(); // blocks forever
 //! }
 //! ```
 //! ## Module Overview
+//! - [`cookie`] - [`CookieBuilder`](cookie::CookieBuilder) and [`CookieJar`](cookie::CookieJar) for `Set-Cookie` headers.
+//! - [`cors`] - Cross-Origin Resource Sharing policies.
+//! - [`error`] - The [`RxError`] type returned by fallible handlers.
+//! - `gzip` - A dependency-free gzip encoder for response compression (`gzip` feature).
+//! - [`json`] - A minimal, dependency-free [`Json`] value and parser.
+//! - [`mime`] - `Content-Type` guessing from file extensions.
+//! - [`parser`] - HTTP/1.x request parsing over any `BufRead` source.
+//! - [`pool`] - The worker thread pool and its [`QueuePolicy`](pool::QueuePolicy).
 //! - [`request`] - Defines the [`Request`] struct for accessing request data.
 //! - [`response`] - Defines the [`Response`] struct for sending responses.
 //! - [`route`] - Defines a single route with path, method, and handler.
-//! - [`router`] - Handles route registration and request dispatching.
+//! - [`router`] - The [`Router`] that registers routes and dispatches requests; usable standalone.
 //! - [`server`] - The main [`Server`] struct to run the HTTP server.
 //! - [`status`] - Standard HTTP status codes as [`HttpStatus`] enum.
+//! - `test_util` - A loopback `TestClient` for end-to-end tests (`test-util` feature).
 //!
 //! ## Request Helpers
 //! The [`Request`](crate::Request) struct provides convenient helpers for
@@ -72,7 +81,7 @@
 //!         }
 //!     });
 //!
-//!     app.run();
+//!     app.run_or_panic();
 //! }
 //! ```
 //!
@@ -107,7 +116,7 @@
 //!         }
 //!     });
 //!
-//!     app.run();
+//!     app.run_or_panic();
 //! }
 //! ```
 //!
@@ -142,7 +151,42 @@
 //!         }
 //!     });
 //!
-//!     app.run();
+//!     app.run_or_panic();
+//! }
+//! ```
+//!
+//! ### Cookies
+//!
+//! ```no_run
+//! use rxpress::Server;
+//!
+//! fn main() {
+//!     let mut app = Server::new("3000");
+//!
+//!     // cookie()
+//!     app.get("/me", |req, res| {
+//!         if let Some(session) = req.cookie("session") {
+//!             res.send(&format!("Session: {}", session));
+//!         } else {
+//!             res.send("Not logged in");
+//!         }
+//!     });
+//!
+//!     // cookie_or()
+//!     app.get("/theme", |req, res| {
+//!         let theme = req.cookie_or("theme", "light");
+//!         res.send(&format!("Theme: {}", theme));
+//!     });
+//!
+//!     // cookie_expect()
+//!     app.get("/account", |req, res| {
+//!         match req.cookie_expect("session") {
+//!             Ok(session) => res.send(&format!("Account for session {}", session)),
+//!             Err(err) => res.status(401).send(&err),
+//!         }
+//!     });
+//!
+//!     app.run_or_panic();
 //! }
 //! ```
 //!
@@ -159,7 +203,7 @@
 //!            .send("Custom header sent!");
 //!     });
 //!
-//!     app.run();
+//!     app.run_or_panic();
 //! }
 //! ```
 //!
@@ -185,7 +229,7 @@
 //!     res.send("Ignored response"); // Will print warning
 //! });
 //!
-//! app.run();
+//! app.run_or_panic();
 //! # }
 //! ```
 //!
@@ -200,7 +244,7 @@
 //!     res.json(r#"{"ignored": true}"#); // Will print warning
 //! });
 //!
-//! app.run();
+//! app.run_or_panic();
 //! # }
 //! ```
 //!
@@ -215,7 +259,7 @@
 //!     res.send("Ignored"); // Will print warning
 //! });
 //!
-//! app.run();
+//! app.run_or_panic();
 //! # }
 //! ```
 //!
@@ -233,7 +277,7 @@
 //!         res.html("<h1>Hello from rxpress!</h1><p>This is HTML.</p>");
 //!     });
 //!
-//!     app.run();
+//!     app.run_or_panic();
 //! }
 //! ```
 //!
@@ -256,7 +300,7 @@
 //!         res.html_file("about.html");
 //!     });
 //!
-//!     app.run();
+//!     app.run_or_panic();
 //! }
 //! ```
 //!
@@ -285,18 +329,33 @@
 //!         res.send("Allowed methods: GET, POST, OPTIONS");
 //!     });
 //!
-//!     app.run();
+//!     app.run_or_panic();
 //! }
 //! ```
 
+pub mod cookie;
+pub mod cors;
+pub mod error;
+#[cfg(any(test, feature = "gzip"))]
+pub mod gzip;
+pub mod json;
+pub mod mime;
+pub mod parser;
+pub mod pool;
 pub mod request;
 pub mod response;
 pub mod route;
 pub mod router;
 pub mod server;
 pub mod status;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 
-pub use request::Request;
+pub use error::RxError;
+pub use json::Json;
+pub use parser::url_decode;
+pub use request::{BodyReader, FromParams, Request};
 pub use response::Response;
+pub use router::Router;
 pub use server::Server;
-pub use status::HttpStatus;
+pub use status::{HttpStatus, StatusClass};
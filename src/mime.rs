@@ -0,0 +1,82 @@
+//! # MIME Module
+//!
+//! Guesses a `Content-Type` from a file extension, used when serving files.
+//!
+//! ## Example
+//! ```
+//! use rxpress::mime;
+//!
+//! assert_eq!(mime::from_path("public/app.js"), "text/javascript; charset=utf-8");
+//! assert_eq!(mime::from_path("photo.PNG"), "image/png");
+//! assert_eq!(mime::from_path("archive.unknown"), "application/octet-stream");
+//! ```
+
+/// Returns the MIME type for `path` based on its extension (case-insensitive).
+///
+/// Unknown or missing extensions map to `application/octet-stream`.
+pub fn from_path(path: &str) -> &'static str {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    let ext = match file_name.rsplit_once('.') {
+        Some((_, ext)) => ext.to_ascii_lowercase(),
+        None => return "application/octet-stream",
+    };
+
+    match ext.as_str() {
+        // text
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "txt" => "text/plain; charset=utf-8",
+        "csv" => "text/csv; charset=utf-8",
+        "md" => "text/markdown; charset=utf-8",
+        "xml" => "application/xml",
+        "json" => "application/json",
+        "map" => "application/json",
+        "wasm" => "application/wasm",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+
+        // images
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+
+        // fonts
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+
+        // audio / video
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // TEST - extension lookup
+    #[test]
+    fn test_from_path() {
+        assert_eq!(from_path("index.html"), "text/html; charset=utf-8");
+        assert_eq!(from_path("/static/css/site.CSS"), "text/css; charset=utf-8");
+        assert_eq!(from_path("img/logo.svg"), "image/svg+xml");
+        assert_eq!(
+            from_path("dir.with.dots/README"),
+            "application/octet-stream"
+        );
+        assert_eq!(from_path("noext"), "application/octet-stream");
+    }
+}
@@ -0,0 +1,690 @@
+//! # Parser Module
+//!
+//! HTTP/1.x request parsing over any [`BufRead`] source.
+//!
+//! The server uses these functions on its TCP connections, but they work the same
+//! over unix sockets, TLS streams, or in-memory buffers, which makes them handy
+//! for custom transports and tests. Errors carry the [`HttpStatus`] to answer with.
+//!
+//! ## Example
+//! ```
+//! use std::io::Cursor;
+//! use rxpress::parser;
+//!
+//! let mut input = Cursor::new("POST /items?id=1 HTTP/1.1\r\nContent-Length: 2\r\n\r\nhi");
+//! let req = parser::parse_request(&mut input, &parser::RequestLimits::default())
+//!     .unwrap()
+//!     .unwrap();
+//!
+//! assert_eq!(req.path, "/items");
+//! assert_eq!(req.query("id"), Some(&"1".to_string()));
+//! assert_eq!(req.body, "hi");
+//! ```
+
+use std::collections::HashMap;
+use std::io::{BufRead, Read};
+
+use crate::request::Request;
+use crate::status::HttpStatus;
+
+/// Default cap on a single request or header line, in bytes (16 KiB).
+pub const DEFAULT_MAX_LINE_BYTES: usize = 16 * 1024;
+
+/// How header lines folded onto a continuation line (starting with a space or
+/// tab, obsoleted by RFC 9112) are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HeaderFolding {
+    /// Answer `400 Bad Request`
+    #[default]
+    Reject,
+    /// Join the continuation onto the previous header's value with a space
+    Unfold,
+}
+
+/// What happens to a body larger than the limits in [`RequestLimits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BodyOverflow {
+    /// Answer `413 Payload Too Large`
+    #[default]
+    Reject,
+    /// Keep the bytes up to the limit and discard the rest, flagging the request
+    /// (see [`Request::body_truncated`](crate::Request::body_truncated))
+    Truncate,
+}
+
+/// Limits applied while parsing a request, set with
+/// [`Server::limits`](crate::Server::limits).
+///
+/// # Example
+/// ```
+/// use rxpress::parser::RequestLimits;
+///
+/// let limits = RequestLimits {
+///     max_header_count: 32,
+///     max_body_bytes: 64 * 1024,
+///     ..RequestLimits::default()
+/// };
+/// assert_eq!(limits.max_line_bytes, 16 * 1024);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestLimits {
+    /// Longest request or header line, in bytes (`414` / `431`); default 16 KiB
+    pub max_line_bytes: usize,
+    /// Longest request target, in bytes (`414`); default 8 KiB
+    pub max_uri_length: usize,
+    /// Most header fields per request (`431`); default 100
+    pub max_header_count: usize,
+    /// Largest header section, in bytes (`431`); default 64 KiB
+    pub max_header_bytes: usize,
+    /// Largest `Content-Length` accepted (`413`); default 1 MiB
+    pub max_body_bytes: usize,
+    /// Most body bytes read in total, whatever the framing, so a chunked body
+    /// cannot grow without end (`413`); default 1 MiB
+    pub max_total_body: usize,
+    /// What to do with folded header lines (`400` when rejected); default
+    /// [`HeaderFolding::Reject`]
+    pub header_folding: HeaderFolding,
+    /// What to do with a body over `max_body_bytes` or `max_total_body`;
+    /// default [`BodyOverflow::Reject`]
+    pub body_overflow: BodyOverflow,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        RequestLimits {
+            max_line_bytes: DEFAULT_MAX_LINE_BYTES,
+            max_uri_length: 8 * 1024,
+            max_header_count: 100,
+            max_header_bytes: 64 * 1024,
+            max_body_bytes: 1024 * 1024,
+            max_total_body: 1024 * 1024,
+            header_folding: HeaderFolding::Reject,
+            body_overflow: BodyOverflow::Reject,
+        }
+    }
+}
+
+/// Reads a complete request (request line, headers, and body).
+///
+/// Returns `Ok(None)` when the reader is at EOF before a request starts.
+pub fn parse_request<R: BufRead>(
+    reader: &mut R,
+    limits: &RequestLimits,
+) -> Result<Option<Request>, HttpStatus> {
+    let request_line = match get_request_line(reader, limits)? {
+        Some(line) => line,
+        None => return Ok(None),
+    };
+    let headers = get_headers(reader, limits)?;
+    let (body, truncated) = read_body(reader, &headers, limits)?;
+
+    let mut req = Request::new(&request_line, headers, body);
+    req.set_body_truncated(truncated);
+    req.set_body_limit(limits.max_body_bytes.min(limits.max_total_body));
+    Ok(Some(req))
+}
+
+/// Reads the request line (`GET /path HTTP/1.1`), skipping leading empty lines.
+///
+/// Returns `Ok(None)` at EOF and `Err(414)` when the line or its target is too long.
+pub fn get_request_line<R: BufRead>(
+    reader: &mut R,
+    limits: &RequestLimits,
+) -> Result<Option<String>, HttpStatus> {
+    loop {
+        let line = match read_line(reader, limits.max_line_bytes) {
+            Ok(Some(line)) => line,
+            Ok(None) => return Ok(None),
+            Err(_) => return Err(HttpStatus::UriTooLong),
+        };
+        // tolerate empty lines before the request line (RFC 7230 3.5)
+        if line.is_empty() {
+            continue;
+        }
+
+        let target = line.split_whitespace().nth(1).unwrap_or("");
+        if target.len() > limits.max_uri_length {
+            return Err(HttpStatus::UriTooLong);
+        }
+        return Ok(Some(line));
+    }
+}
+
+/// Reads headers up to the empty line, with lowercase keys.
+///
+/// Returns `Err(431)` when a line, the header count, or the header section
+/// exceeds its limit, and `Err(400)` for a folded line unless
+/// [`HeaderFolding::Unfold`] is set (or when there is no header to continue),
+/// or for repeated `Content-Length` headers with different values.
+pub fn get_headers<R: BufRead>(
+    reader: &mut R,
+    limits: &RequestLimits,
+) -> Result<HashMap<String, String>, HttpStatus> {
+    let mut map: HashMap<String, String> = HashMap::new();
+    let (mut count, mut bytes) = (0, 0);
+    let mut last_key: Option<String> = None;
+
+    while let Some(line) = read_line(reader, limits.max_line_bytes)? {
+        // no header -> break the loop
+        if line.is_empty() {
+            break;
+        }
+
+        count += 1;
+        bytes += line.len() + 2;
+        if count > limits.max_header_count || bytes > limits.max_header_bytes {
+            return Err(HttpStatus::RequestHeaderFieldsTooLarge);
+        }
+
+        // obs-fold: a continuation of the previous header's value
+        if line.starts_with([' ', '\t']) {
+            let value = match (limits.header_folding, &last_key) {
+                (HeaderFolding::Unfold, Some(key)) => map.get_mut(key),
+                _ => None,
+            };
+            match value {
+                Some(value) => {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+                None => return Err(HttpStatus::BadRequest),
+            }
+            continue;
+        }
+
+        // split headers with ':' & store as key-value pair
+        if let Some((key, val)) = line.split_once(":") {
+            let key = key.trim().to_string().to_ascii_lowercase(); //put keys as lowercase
+            let val = val.trim().to_string();
+            // repeated lengths that disagree leave the body boundary ambiguous
+            if key == "content-length" && map.get(&key).is_some_and(|prev| *prev != val) {
+                return Err(HttpStatus::BadRequest);
+            }
+            map.insert(key.clone(), val);
+            last_key = Some(key);
+        }
+    }
+
+    Ok(map)
+}
+
+/// Reads the body announced by the `content-length` header, or decodes a
+/// `transfer-encoding: chunked` body.
+///
+/// Returns `Err(413)` when the announced length exceeds `max_body_bytes` (before
+/// anything is allocated) or the body grows past `max_total_body`, and
+/// `Err(400)` when the connection ends before the full body arrived, the chunk
+/// framing is malformed, `Content-Length` is not a number, or it comes together
+/// with `Transfer-Encoding`. Chunk trailers count against the header limits
+/// (`Err(431)`). Bodies with a `content-encoding` other than `identity` get
+/// `Err(415)`, since no decoders are built in.
+///
+/// With [`BodyOverflow::Truncate`] an oversized body is cut at the limit instead
+/// of rejected; use [`read_body`] to learn whether that happened.
+pub fn get_body<R: BufRead>(
+    reader: &mut R,
+    headers: &HashMap<String, String>,
+    limits: &RequestLimits,
+) -> Result<String, HttpStatus> {
+    read_body(reader, headers, limits).map(|(body, _)| body)
+}
+
+/// Like [`get_body`], also returning `true` when the body was cut off under
+/// [`BodyOverflow::Truncate`]. The discarded bytes are still read off the
+/// connection, so the next request on it parses cleanly.
+///
+/// # Example
+/// ```
+/// use std::collections::HashMap;
+/// use rxpress::parser::{self, BodyOverflow, RequestLimits};
+///
+/// let limits = RequestLimits {
+///     max_body_bytes: 4,
+///     body_overflow: BodyOverflow::Truncate,
+///     ..RequestLimits::default()
+/// };
+/// let headers = HashMap::from([("content-length".to_string(), "6".to_string())]);
+/// let body = parser::read_body(&mut "abcdef".as_bytes(), &headers, &limits).unwrap();
+/// assert_eq!(body, ("abcd".to_string(), true));
+/// ```
+pub fn read_body<R: BufRead>(
+    reader: &mut R,
+    headers: &HashMap<String, String>,
+    limits: &RequestLimits,
+) -> Result<(String, bool), HttpStatus> {
+    let max = limits.max_body_bytes.min(limits.max_total_body);
+    let truncate = limits.body_overflow == BodyOverflow::Truncate;
+    let content_length = headers.get("content-length");
+
+    // ambiguous framing would let a kept-alive connection smuggle a request
+    if content_length.is_some() && headers.contains_key("transfer-encoding") {
+        return Err(HttpStatus::BadRequest);
+    }
+
+    if headers
+        .get("transfer-encoding")
+        .is_some_and(|te| te.to_ascii_lowercase().contains("chunked"))
+    {
+        check_encoding(headers)?;
+        let (body, truncated) = read_chunked(reader, limits)?;
+        return Ok((String::from_utf8_lossy(&body).to_string(), truncated));
+    }
+
+    // find the body with 'content-length' key
+    if let Some(len) = content_length {
+        let size = match len.parse::<usize>() {
+            Ok(size) if len.bytes().all(|b| b.is_ascii_digit()) => size,
+            _ => return Err(HttpStatus::BadRequest),
+        };
+        if size > max && !truncate {
+            return Err(HttpStatus::PayloadTooLarge);
+        }
+        if size > 0 {
+            check_encoding(headers)?;
+        }
+        let mut buffer = vec![0; size.min(max)];
+        reader
+            .read_exact(&mut buffer)
+            .map_err(|_| HttpStatus::BadRequest)?;
+        if size > max {
+            discard(reader, size - max)?;
+        }
+        return Ok((String::from_utf8_lossy(&buffer).to_string(), size > max));
+    }
+
+    Ok((String::new(), false))
+}
+
+/// Decodes `%XX` escapes, e.g. in path segments. The decoded bytes are read as
+/// UTF-8; malformed escapes (`%ZZ`, a trailing `%`) are kept literally.
+///
+/// `+` is left alone here; query strings and form bodies additionally read it
+/// as a space.
+///
+/// # Example
+/// ```
+/// assert_eq!(rxpress::url_decode("caf%C3%A9%20au+lait"), "café au+lait");
+/// assert_eq!(rxpress::url_decode("100%"), "100%");
+/// ```
+pub fn url_decode(input: &str) -> String {
+    decode(input, false)
+}
+
+/// Decodes a query-string or form component: `+` as space, then `%XX` escapes.
+pub(crate) fn decode_component(input: &str) -> String {
+    decode(input, true)
+}
+
+/*---- Private Functions ----*/
+/// Percent-decodes `input`, optionally reading `+` as a space.
+fn decode(input: &str, plus_as_space: bool) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+            (b'+', _) if plus_as_space => out.push(b' '),
+            (byte, _) => out.push(byte),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Fails with `415` unless every listed `content-encoding` is `identity`.
+fn check_encoding(headers: &HashMap<String, String>) -> Result<(), HttpStatus> {
+    let supported = headers.get("content-encoding").is_none_or(|encodings| {
+        encodings
+            .split(',')
+            .all(|e| e.trim().is_empty() || e.trim().eq_ignore_ascii_case("identity"))
+    });
+    if supported {
+        Ok(())
+    } else {
+        Err(HttpStatus::UnsupportedMediaType)
+    }
+}
+
+/// Reads and drops `len` body bytes, failing with `400` if the connection ends first.
+fn discard<R: BufRead>(reader: &mut R, len: usize) -> Result<(), HttpStatus> {
+    let copied = std::io::copy(&mut reader.take(len as u64), &mut std::io::sink())
+        .map_err(|_| HttpStatus::BadRequest)?;
+    if copied < len as u64 {
+        return Err(HttpStatus::BadRequest);
+    }
+    Ok(())
+}
+
+/// Decodes a chunked body: hex sizes (extensions after `;` are ignored), each
+/// chunk followed by CRLF, up to the zero-size chunk and optional trailers.
+/// Stops with `Err(413)` before reading a chunk that would take the body past
+/// `max_total_body`, unless [`BodyOverflow::Truncate`] is set: then the excess
+/// is discarded and the second value is `true`.
+///
+/// Size and trailer lines are capped at `max_line_bytes`, and trailers count
+/// against `max_header_count` and `max_header_bytes` like headers (`Err(431)`).
+fn read_chunked<R: BufRead>(
+    reader: &mut R,
+    limits: &RequestLimits,
+) -> Result<(Vec<u8>, bool), HttpStatus> {
+    let max = limits.max_total_body;
+    let truncate = limits.body_overflow == BodyOverflow::Truncate;
+    let mut body = Vec::new();
+    let mut truncated = false;
+
+    loop {
+        let line = read_line(reader, limits.max_line_bytes)
+            .ok()
+            .flatten()
+            .ok_or(HttpStatus::BadRequest)?;
+        let size = line.split(';').next().unwrap_or("").trim();
+        let size = usize::from_str_radix(size, 16).map_err(|_| HttpStatus::BadRequest)?;
+
+        if size == 0 {
+            break;
+        }
+
+        let room = max.saturating_sub(body.len());
+        if size > room && !truncate {
+            return Err(HttpStatus::PayloadTooLarge);
+        }
+
+        let start = body.len();
+        body.resize(start + size.min(room), 0);
+        reader
+            .read_exact(&mut body[start..])
+            .map_err(|_| HttpStatus::BadRequest)?;
+        if size > room {
+            discard(reader, size - room)?;
+            truncated = true;
+        }
+
+        // every chunk ends with its own CRLF
+        if !matches!(read_line(reader, 0), Ok(Some(line)) if line.is_empty()) {
+            return Err(HttpStatus::BadRequest);
+        }
+    }
+
+    // skip trailer fields up to the final empty line
+    let (mut count, mut bytes) = (0, 0);
+    loop {
+        match read_line(reader, limits.max_line_bytes)? {
+            Some(line) if line.is_empty() => return Ok((body, truncated)),
+            Some(line) => {
+                count += 1;
+                bytes += line.len() + 2;
+                if count > limits.max_header_count || bytes > limits.max_header_bytes {
+                    return Err(HttpStatus::RequestHeaderFieldsTooLarge);
+                }
+            }
+            None => return Err(HttpStatus::BadRequest),
+        }
+    }
+}
+
+/// Reads one line without its trailing CRLF; `None` on EOF or error,
+/// `Err(431)` once the line exceeds `limit`.
+fn read_line<R: BufRead>(reader: &mut R, limit: usize) -> Result<Option<String>, HttpStatus> {
+    let mut line = Vec::new();
+
+    // +2 leaves room for the CRLF of a line that is exactly at the limit
+    match reader
+        .by_ref()
+        .take(limit as u64 + 2)
+        .read_until(b'\n', &mut line)
+    {
+        Ok(0) | Err(_) => return Ok(None),
+        Ok(_) => {}
+    }
+
+    while let Some(b'\r' | b'\n') = line.last() {
+        line.pop();
+    }
+    if line.len() > limit {
+        return Err(HttpStatus::RequestHeaderFieldsTooLarge);
+    }
+
+    Ok(Some(String::from_utf8_lossy(&line).to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    // TEST - parse a full request from memory
+    #[test]
+    fn test_parse_request_from_cursor() {
+        let raw =
+            "\r\nPUT /users/7 HTTP/1.1\r\nHost: example.com\r\nContent-Length: 5\r\n\r\nhello";
+        let mut input = Cursor::new(raw);
+
+        let req = parse_request(&mut input, &RequestLimits::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(req.method, "PUT");
+        assert_eq!(req.path, "/users/7");
+        assert_eq!(req.header("host"), Some(&"example.com".to_string()));
+        assert_eq!(req.body, "hello");
+
+        // nothing left -> EOF
+        assert!(
+            parse_request(&mut input, &RequestLimits::default())
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    // TEST - chunked bodies are reassembled
+    #[test]
+    fn test_chunked_body() {
+        let raw = "POST /upload HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n\
+                   5;name=first\r\nhello\r\n7\r\n, world\r\n0\r\nX-Trailer: 1\r\n\r\n\
+                   GET /next HTTP/1.1\r\n\r\n";
+        let mut input = Cursor::new(raw);
+
+        let req = parse_request(&mut input, &RequestLimits::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(req.body, "hello, world");
+
+        // the framing was consumed exactly, so the next request parses cleanly
+        let next = parse_request(&mut input, &RequestLimits::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(next.path, "/next");
+
+        let mut input = Cursor::new("POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\n");
+        assert_eq!(
+            parse_request(&mut input, &RequestLimits::default()).err(),
+            Some(HttpStatus::BadRequest)
+        );
+    }
+
+    // TEST - each limit is enforced with its status
+    #[test]
+    fn test_parse_errors() {
+        let limits = RequestLimits {
+            max_line_bytes: 64,
+            max_uri_length: 32,
+            max_header_count: 2,
+            max_header_bytes: 40,
+            max_body_bytes: 8,
+            max_total_body: 16,
+            header_folding: HeaderFolding::Reject,
+            body_overflow: BodyOverflow::Reject,
+        };
+        let parse = |raw: String| parse_request(&mut Cursor::new(raw), &limits).err();
+
+        let big_header = format!("GET / HTTP/1.1\r\nX-Big: {}\r\n\r\n", "a".repeat(100));
+        assert_eq!(
+            parse(big_header),
+            Some(HttpStatus::RequestHeaderFieldsTooLarge)
+        );
+
+        let long_line = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(100));
+        assert_eq!(parse(long_line), Some(HttpStatus::UriTooLong));
+
+        let long_uri = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(40));
+        assert_eq!(parse(long_uri), Some(HttpStatus::UriTooLong));
+
+        let many = "GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\nC: 3\r\n\r\n".to_string();
+        assert_eq!(parse(many), Some(HttpStatus::RequestHeaderFieldsTooLarge));
+
+        let section = format!(
+            "GET / HTTP/1.1\r\nA: {}\r\nB: {}\r\n\r\n",
+            "x".repeat(20),
+            "y".repeat(20)
+        );
+        assert_eq!(
+            parse(section),
+            Some(HttpStatus::RequestHeaderFieldsTooLarge)
+        );
+
+        // rejected from the declared length alone, before any allocation
+        let body = "POST / HTTP/1.1\r\nContent-Length: 999999999999\r\n\r\n".to_string();
+        assert_eq!(parse(body), Some(HttpStatus::PayloadTooLarge));
+
+        let ok = "POST / HTTP/1.1\r\nContent-Length: 8\r\n\r\n12345678".to_string();
+        assert_eq!(parse(ok), None);
+
+        let truncated = "POST / HTTP/1.1\r\nContent-Length: 6\r\n\r\nshort".to_string();
+        assert_eq!(parse(truncated), Some(HttpStatus::BadRequest));
+
+        // chunked bodies have no declared length, only the running total counts
+        let chunked = format!(
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n{}0\r\n\r\n",
+            "4\r\nabcd\r\n".repeat(4)
+        );
+        assert_eq!(parse(chunked), None);
+        let endless = format!(
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n{}",
+            "4\r\nabcd\r\n".repeat(100)
+        );
+        assert_eq!(parse(endless), Some(HttpStatus::PayloadTooLarge));
+
+        // chunk framing follows the configured line and header limits
+        let chunked = |framing: String| {
+            format!(
+                "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n{}",
+                framing
+            )
+        };
+        let long_size = chunked(format!("4;{}\r\nabcd\r\n0\r\n\r\n", "x".repeat(100)));
+        assert_eq!(parse(long_size), Some(HttpStatus::BadRequest));
+        let long_trailer = chunked(format!("0\r\nX-T: {}\r\n\r\n", "t".repeat(100)));
+        assert_eq!(
+            parse(long_trailer),
+            Some(HttpStatus::RequestHeaderFieldsTooLarge)
+        );
+        let many_trailers = chunked("0\r\nA: 1\r\nB: 2\r\nC: 3\r\n\r\n".to_string());
+        assert_eq!(
+            parse(many_trailers),
+            Some(HttpStatus::RequestHeaderFieldsTooLarge)
+        );
+        let trailer_bytes = chunked(format!(
+            "0\r\nA: {}\r\nB: {}\r\n\r\n",
+            "x".repeat(20),
+            "y".repeat(20)
+        ));
+        assert_eq!(
+            parse(trailer_bytes),
+            Some(HttpStatus::RequestHeaderFieldsTooLarge)
+        );
+        let ok = chunked("4\r\nabcd\r\n0\r\nA: 1\r\n\r\n".to_string());
+        assert_eq!(parse(ok), None);
+    }
+
+    // TEST - folded header lines are rejected, or unfolded when allowed
+    #[test]
+    fn test_header_folding() {
+        let folded = "GET / HTTP/1.1\r\nX-Note: first\r\n  second\r\n\tthird\r\nHost: a\r\n\r\n";
+
+        let strict = RequestLimits::default();
+        let err = parse_request(&mut Cursor::new(folded), &strict).err();
+        assert_eq!(err, Some(HttpStatus::BadRequest));
+
+        let lenient = RequestLimits {
+            header_folding: HeaderFolding::Unfold,
+            ..RequestLimits::default()
+        };
+        let req = parse_request(&mut Cursor::new(folded), &lenient)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            req.header("x-note"),
+            Some(&"first second third".to_string())
+        );
+        assert_eq!(req.header("host"), Some(&"a".to_string()));
+
+        // nothing to continue
+        let orphan = "GET / HTTP/1.1\r\n folded\r\n\r\n";
+        let err = parse_request(&mut Cursor::new(orphan), &lenient).err();
+        assert_eq!(err, Some(HttpStatus::BadRequest));
+    }
+
+    // TEST - a chunked body over the limit is cut and the rest of it skipped
+    #[test]
+    fn test_truncate_chunked_body() {
+        let limits = RequestLimits {
+            max_total_body: 5,
+            body_overflow: BodyOverflow::Truncate,
+            ..RequestLimits::default()
+        };
+        let raw = "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n4\r\ndefg\r\n2\r\nhi\r\n0\r\n\r\nGET /next HTTP/1.1\r\n\r\n";
+        let mut input = Cursor::new(raw);
+
+        let req = parse_request(&mut input, &limits).unwrap().unwrap();
+        assert_eq!(req.body, "abcde");
+        assert!(req.body_truncated());
+
+        let next = parse_request(&mut input, &limits).unwrap().unwrap();
+        assert_eq!(next.path, "/next");
+        assert!(!next.body_truncated());
+    }
+
+    // TEST - only identity-encoded bodies are accepted
+    #[test]
+    fn test_content_encoding() {
+        let parse =
+            |raw: &str| parse_request(&mut Cursor::new(raw), &RequestLimits::default()).err();
+
+        let encoded = "POST / HTTP/1.1\r\nContent-Encoding: br\r\nContent-Length: 4\r\n\r\n\x0bxyz";
+        assert_eq!(parse(encoded), Some(HttpStatus::UnsupportedMediaType));
+
+        let chunked = "POST / HTTP/1.1\r\nContent-Encoding: gzip\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n";
+        assert_eq!(parse(chunked), Some(HttpStatus::UnsupportedMediaType));
+
+        let identity =
+            "POST / HTTP/1.1\r\nContent-Encoding: identity\r\nContent-Length: 2\r\n\r\nhi";
+        assert_eq!(parse(identity), None);
+
+        // nothing to decode without a body
+        assert_eq!(
+            parse("GET / HTTP/1.1\r\nContent-Encoding: br\r\n\r\n"),
+            None
+        );
+    }
+
+    // TEST - percent-decoding is UTF-8 aware and leaves malformed escapes alone
+    #[test]
+    fn test_url_decode() {
+        assert_eq!(url_decode("hello%20world"), "hello world");
+        assert_eq!(url_decode("%E2%9C%93%2Fok"), "✓/ok");
+        assert_eq!(url_decode("%ZZ%4"), "%ZZ%4");
+        assert_eq!(url_decode("a+b%2B"), "a+b+");
+        assert_eq!(decode_component("a+b%2B"), "a b+");
+        assert_eq!(url_decode("%FF"), "\u{FFFD}");
+    }
+}
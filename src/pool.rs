@@ -0,0 +1,165 @@
+//! # Pool Module
+//!
+//! The worker pool behind [`Server::workers`](crate::Server::workers). Accepted
+//! connections are queued for a fixed set of worker threads; the [`QueuePolicy`]
+//! decides what happens when every worker is busy and the queue is full.
+//!
+//! ## Example
+//! ```no_run
+//! use rxpress::Server;
+//! use rxpress::pool::QueuePolicy;
+//!
+//! fn main() {
+//!     let mut app = Server::new("3000");
+//!     app.workers(8);
+//!     app.queue_policy(QueuePolicy::Reject(128)); // 503 once 128 connections wait
+//!
+//!     app.get("/", |_req, res| {
+//!         res.send("Hello from a worker!");
+//!     });
+//!
+//!     app.run_or_panic();
+//! }
+//! ```
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TrySendError};
+use std::sync::{Mutex, RwLock};
+use std::thread::Scope;
+
+/// What to do with a new connection when all workers are busy and the queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueuePolicy {
+    /// Queue up to `n` connections, then block the accept loop until a slot frees up
+    Block(usize),
+    /// Queue up to `n` connections, then answer `503 Service Unavailable`
+    Reject(usize),
+    /// Queue without limit
+    Unbounded,
+}
+
+impl Default for QueuePolicy {
+    fn default() -> Self {
+        QueuePolicy::Block(64)
+    }
+}
+
+/// A fixed set of worker threads fed through an mpsc job queue.
+///
+/// Workers are spawned on a [`thread::scope`](std::thread::scope), so jobs may
+/// borrow from the server instead of needing `'static` state.
+pub(crate) struct ThreadPool<T> {
+    size: usize,
+    /// `None` once the pool is closed
+    sender: RwLock<Option<QueueSender<T>>>,
+    receiver: Mutex<Receiver<T>>,
+}
+
+enum QueueSender<T> {
+    /// A capped queue; `true` hands jobs back instead of blocking when full
+    Bounded(SyncSender<T>, bool),
+    Unbounded(Sender<T>),
+}
+
+impl<T: Send> ThreadPool<T> {
+    /// Creates a pool of `size` workers whose queue follows `policy`.
+    pub(crate) fn new(size: usize, policy: QueuePolicy) -> ThreadPool<T> {
+        let (sender, receiver) = match policy {
+            QueuePolicy::Block(n) | QueuePolicy::Reject(n) => {
+                let (tx, rx) = mpsc::sync_channel(n);
+                let reject = matches!(policy, QueuePolicy::Reject(_));
+                (QueueSender::Bounded(tx, reject), rx)
+            }
+            QueuePolicy::Unbounded => {
+                let (tx, rx) = mpsc::channel();
+                (QueueSender::Unbounded(tx), rx)
+            }
+        };
+
+        ThreadPool {
+            size,
+            sender: RwLock::new(Some(sender)),
+            receiver: Mutex::new(receiver),
+        }
+    }
+
+    /// Spawns the workers on `scope`, each running `work` on the jobs it receives.
+    ///
+    /// A job that panics is dropped (closing its connection) and the worker
+    /// moves on to the next one, so panicking handlers can't drain the pool.
+    pub(crate) fn start<'scope, F>(&'scope self, scope: &'scope Scope<'scope, '_>, work: &'scope F)
+    where
+        F: Fn(T) + Sync,
+    {
+        for _ in 0..self.size {
+            scope.spawn(move || {
+                loop {
+                    // hold the lock only while waiting for the next job
+                    let job = match self.receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => return,
+                    };
+                    match job {
+                        Ok(job) => {
+                            if panic::catch_unwind(AssertUnwindSafe(|| work(job))).is_err() {
+                                eprintln!(
+                                    "[rxpress error]: a handler panicked, closing its connection."
+                                );
+                            }
+                        }
+                        Err(_) => return,
+                    }
+                }
+            });
+        }
+    }
+
+    /// Queues a job, handing it back if the queue is full under
+    /// [`QueuePolicy::Reject`] or the pool is closed.
+    pub(crate) fn execute(&self, job: T) -> Result<(), T> {
+        let sender = match self.sender.read() {
+            Ok(sender) => sender,
+            Err(_) => return Err(job),
+        };
+        match sender.as_ref() {
+            None => Err(job),
+            Some(QueueSender::Bounded(tx, true)) => match tx.try_send(job) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(job) | TrySendError::Disconnected(job)) => Err(job),
+            },
+            Some(QueueSender::Bounded(tx, false)) => tx.send(job).map_err(|err| err.0),
+            Some(QueueSender::Unbounded(tx)) => tx.send(job).map_err(|err| err.0),
+        }
+    }
+
+    /// Stops taking jobs; workers exit once the queued ones are done.
+    pub(crate) fn close(&self) {
+        if let Ok(mut sender) = self.sender.write() {
+            sender.take();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // TEST - a full queue hands jobs back only under `Reject`
+    #[test]
+    fn test_execute_when_full() {
+        // no workers are started, so nothing drains the queue
+        let pool = ThreadPool::new(1, QueuePolicy::Reject(2));
+        assert_eq!(pool.execute(1), Ok(()));
+        assert_eq!(pool.execute(2), Ok(()));
+        assert_eq!(pool.execute(3), Err(3));
+
+        let pool = ThreadPool::new(1, QueuePolicy::Unbounded);
+        for n in 0..1000 {
+            assert_eq!(pool.execute(n), Ok(()));
+        }
+
+        // a closed pool hands every job back
+        pool.close();
+        assert_eq!(pool.execute(1000), Err(1000));
+    }
+}
@@ -73,11 +73,66 @@
 //!         }
 //!     });
 //!
-//!     app.run();
+//!     app.run_or_panic();
 //! }
 //! ```
 
 use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Read};
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+use crate::error::RxError;
+use crate::json::Json;
+use crate::parser::{self, RequestLimits};
+use crate::response::Response;
+use crate::server::ShutdownSignal;
+
+/// The HTTP version from the request line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HttpVersion {
+    /// `HTTP/1.0`
+    Http10,
+    /// `HTTP/1.1`
+    Http11,
+    /// Any other version string, kept verbatim (e.g. `HTTP/2`)
+    Other(String),
+}
+
+impl HttpVersion {
+    /// Parses a version string such as `HTTP/1.1`.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::request::HttpVersion;
+    ///
+    /// assert_eq!(HttpVersion::parse("HTTP/1.0"), HttpVersion::Http10);
+    /// assert_eq!(HttpVersion::parse("HTTP/2").as_str(), "HTTP/2");
+    /// ```
+    pub fn parse(version: &str) -> HttpVersion {
+        match version {
+            "HTTP/1.0" => HttpVersion::Http10,
+            "HTTP/1.1" => HttpVersion::Http11,
+            other => HttpVersion::Other(other.to_string()),
+        }
+    }
+
+    /// Returns the version as written on the wire.
+    pub fn as_str(&self) -> &str {
+        match self {
+            HttpVersion::Http10 => "HTTP/1.0",
+            HttpVersion::Http11 => "HTTP/1.1",
+            HttpVersion::Other(other) => other,
+        }
+    }
+}
+
+impl fmt::Display for HttpVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
 /// Represents an HTTP request.
 ///
@@ -91,12 +146,38 @@ pub struct Request {
     pub headers: HashMap<String, String>,
     /// HTTP version (e.g., `HTTP/1.1`)
     pub version: String,
-    /// Query parameters parsed into key-value pairs
+    /// Query parameters parsed into URL-decoded key-value pairs
     pub query: HashMap<String, String>,
     /// Path parameters extracted from route definitions
     pub params: HashMap<String, String>,
     /// Request body as a string
     pub body: String,
+    /// `version`, parsed once at construction
+    http_version: HttpVersion,
+    /// Query pairs in the order they appeared in the raw query string
+    query_pairs: Vec<(String, String)>,
+    /// The query string as sent, without the `?`
+    raw_query: Option<String>,
+    /// Parameter names declared by the matched route pattern
+    param_names: Vec<String>,
+    /// Decoded segments of a repeating parameter, split before decoding
+    param_segments: HashMap<String, Vec<String>>,
+    /// Cookies parsed from the `Cookie` header
+    cookies: HashMap<String, String>,
+    /// Shared with the server that read this request
+    shutdown: ShutdownSignal,
+    /// Server socket the connection arrived on
+    local_addr: Option<SocketAddr>,
+    /// Client end of the connection
+    peer_addr: Option<SocketAddr>,
+    /// Set when the body was cut off at the configured size limit
+    body_truncated: bool,
+    /// Body still to be read, see [`Request::set_body_stream`]
+    body_stream: Option<Box<dyn Read + Send>>,
+    /// Most bytes [`Request::buffer_body`] reads from `body_stream`
+    body_limit: usize,
+    /// Set once `body_stream` turned out larger than `body_limit`
+    body_too_large: bool,
 }
 
 impl Request {
@@ -118,31 +199,75 @@ impl Request {
     /// assert_eq!(req.path, "/hello");
     /// assert_eq!(req.query("developer"), Some(&"alfaarghy".to_string()));
     /// ```
-    pub fn new(request_line: &str, headers: HashMap<String, String>, body: String) -> Request {
+    pub fn new(request_line: &str, mut headers: HashMap<String, String>, body: String) -> Request {
         let parts: Vec<&str> = request_line.split_whitespace().collect();
 
-        let (method, full_path, version) = match parts.as_slice() {
+        let (method, mut full_path, version) = match parts.as_slice() {
             [m, p, v] => (m.to_string(), p.to_string(), v.to_string()),
             _ => ("GET".to_string(), "/".to_string(), "HTTP/1.1".to_string()),
         };
 
-        let (path, query) = if let Some((p, q)) = full_path.split_once('?') {
-            (p.to_string(), Self::parse_query(q))
+        // absolute-form (`http://host/path`, sent to proxies): route on the path,
+        // and the target's authority replaces the Host header (RFC 7230 5.4)
+        if let Some((host, target)) = Self::split_absolute_form(&full_path) {
+            headers.retain(|k, _| !k.eq_ignore_ascii_case("host"));
+            headers.insert("host".to_string(), host);
+            full_path = target;
+        }
+
+        let (path, raw_query, query_pairs) = if let Some((p, q)) = full_path.split_once('?') {
+            (p.to_string(), Some(q.to_string()), Self::parse_query(q))
         } else {
-            (full_path, HashMap::new())
+            (full_path, None, Vec::new())
         };
+        let mut query = HashMap::new();
+        for (key, value) in &query_pairs {
+            query.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+        let cookies = headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case("cookie"))
+            .map_or_else(HashMap::new, |(_, v)| Self::parse_cookies(v));
 
         Request {
             method,
             path,
             headers,
+            http_version: HttpVersion::parse(&version),
             version,
             query,
+            query_pairs,
+            raw_query,
+            param_names: Vec::new(),
+            param_segments: HashMap::new(),
+            cookies,
+            shutdown: ShutdownSignal::default(),
+            local_addr: None,
+            peer_addr: None,
+            body_truncated: false,
+            body_stream: None,
+            body_limit: RequestLimits::default().max_body_bytes,
+            body_too_large: false,
             params: HashMap::new(),
             body,
         }
     }
 
+    /// Gets the HTTP version parsed from the request line.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    /// use rxpress::request::HttpVersion;
+    ///
+    /// let req = Request::new("GET / HTTP/1.0", HashMap::new(), "".into());
+    /// assert_eq!(req.http_version(), &HttpVersion::Http10);
+    /// ```
+    pub fn http_version(&self) -> &HttpVersion {
+        &self.http_version
+    }
+
     /// Gets a header value by key (case-insensitive).
     ///
     /// # Example
@@ -180,6 +305,26 @@ impl Request {
             .map(|(_, v)| v)
     }
 
+    /// Checks whether header `key` is present and its whole value equals `value`,
+    /// both compared case-insensitively (ASCII).
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let mut headers = HashMap::new();
+    /// headers.insert("Connection".into(), "Keep-Alive".into());
+    /// let req = Request::new("GET / HTTP/1.0", headers, "".into());
+    ///
+    /// assert!(req.header_eq_ignore_case("connection", "keep-alive"));
+    /// assert!(!req.header_eq_ignore_case("connection", "close"));
+    /// ```
+    pub fn header_eq_ignore_case(&self, key: &str, value: &str) -> bool {
+        self.header(key)
+            .is_some_and(|v| v.eq_ignore_ascii_case(value))
+    }
+
     /// Gets a header value or returns a default if not present.
     ///
     /// # Example
@@ -247,6 +392,177 @@ impl Request {
         ))
     }
 
+    /// Splits the `Authorization` header into `(scheme, credentials)` at the first space.
+    ///
+    /// Works for any scheme (`Bearer`, `Basic`, `Token`, `AWS4-HMAC-SHA256`, ...).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.get("/private", |req, res| {
+    ///     match req.auth_scheme() {
+    ///         Some(("Bearer", token)) => res.send(&format!("Bearer token: {}", token)),
+    ///         Some((scheme, _)) => res.status(401).send(&format!("Unsupported scheme: {}", scheme)),
+    ///         None => res.status(401).send("Missing Authorization header"),
+    ///     }
+    /// });
+    /// ```
+    /// ---
+    /// ## Test
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let mut headers = HashMap::new();
+    /// headers.insert("authorization".into(), "Token xyz".into());
+    /// let req = Request::new("GET / HTTP/1.1", headers, "".into());
+    /// assert_eq!(req.auth_scheme(), Some(("Token", "xyz")));
+    /// ```
+    pub fn auth_scheme(&self) -> Option<(&str, &str)> {
+        let value = self.header("Authorization")?.trim();
+        match value.split_once(' ') {
+            Some((scheme, credentials)) => Some((scheme, credentials.trim_start())),
+            None if !value.is_empty() => Some((value, "")),
+            None => None,
+        }
+    }
+
+    /// Returns `true` when the request carries `X-Requested-With: XMLHttpRequest`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.get("/items", |req, res| {
+    ///     if req.is_ajax() {
+    ///         res.html("<li>fragment</li>");
+    ///     } else {
+    ///         res.html("<html><body><ul><li>full page</li></ul></body></html>");
+    ///     }
+    /// });
+    /// ```
+    pub fn is_ajax(&self) -> bool {
+        self.header_eq_ignore_case("X-Requested-With", "XMLHttpRequest")
+    }
+
+    /// Checks whether the `Accept-Encoding` header allows `coding` (e.g. `gzip`),
+    /// by name or through `*`, with a non-zero `q` weight. A named entry wins over
+    /// `*`; without the header nothing is assumed to be accepted.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let mut headers = HashMap::new();
+    /// headers.insert("accept-encoding".into(), "br, GZIP;q=0.8, *;q=0".into());
+    /// let req = Request::new("GET / HTTP/1.1", headers, "".into());
+    ///
+    /// assert!(req.accepts_encoding("gzip"));
+    /// assert!(!req.accepts_encoding("deflate"));
+    /// ```
+    pub fn accepts_encoding(&self, coding: &str) -> bool {
+        let header = match self.header("Accept-Encoding") {
+            Some(header) => header,
+            None => return false,
+        };
+
+        let mut wildcard = None;
+        for part in header.split(',') {
+            let mut pieces = part.split(';');
+            let name = pieces.next().unwrap_or("").trim();
+            let q = pieces
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if name.eq_ignore_ascii_case(coding) {
+                return q > 0.0;
+            }
+            if name == "*" {
+                wildcard = Some(q > 0.0);
+            }
+        }
+        wildcard.unwrap_or(false)
+    }
+
+    /// Picks the best of the offered MIME types according to the `Accept` header.
+    ///
+    /// Honours `q` weights and `type/*` / `*/*` wildcards; ties go to the earlier
+    /// offer. Without an `Accept` header the first offer is returned. Returns `None`
+    /// when the client accepts none of the offers.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.get("/user", |req, res| {
+    ///     match req.accepts(&["text/html", "application/json"]) {
+    ///         Some("application/json") => res.json(r#"{"name":"rxpress"}"#),
+    ///         _ => res.html("<p>rxpress</p>"),
+    ///     }
+    /// });
+    /// ```
+    /// ---
+    /// ## Test
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let mut headers = HashMap::new();
+    /// headers.insert("accept".into(), "application/json, text/*;q=0.5".into());
+    /// let req = Request::new("GET / HTTP/1.1", headers, "".into());
+    ///
+    /// assert_eq!(req.accepts(&["text/plain", "application/json"]), Some("application/json"));
+    /// assert_eq!(req.accepts(&["image/png"]), None);
+    /// ```
+    pub fn accepts<'t>(&self, offers: &[&'t str]) -> Option<&'t str> {
+        let accept = match self.header("Accept") {
+            Some(accept) if !accept.trim().is_empty() => accept,
+            _ => return offers.first().copied(),
+        };
+
+        // (media range, q-value)
+        let ranges: Vec<(&str, f32)> = accept
+            .split(',')
+            .map(|part| {
+                let mut pieces = part.split(';');
+                let range = pieces.next().unwrap_or("").trim();
+                let q = pieces
+                    .filter_map(|p| p.trim().strip_prefix("q="))
+                    .find_map(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (range, q)
+            })
+            .collect();
+
+        let mut best: Option<(&'t str, f32)> = None;
+        for offer in offers {
+            let q = ranges
+                .iter()
+                .filter(|(range, _)| Self::media_matches(range, offer))
+                .map(|(_, q)| *q)
+                .fold(None, |acc: Option<f32>, q| {
+                    Some(acc.map_or(q, |a| a.max(q)))
+                });
+
+            if let Some(q) = q
+                && q > 0.0
+                && best.is_none_or(|(_, best_q)| q > best_q)
+            {
+                best = Some((offer, q));
+            }
+        }
+
+        best.map(|(offer, _)| offer)
+    }
+
     /// Gets a route parameter value (set by the router).
     ///
     /// # Example
@@ -341,7 +657,170 @@ impl Request {
         ))
     }
 
-    /// Gets a query parameter value.
+    /// Parses a route parameter into any [`FromStr`] type; `None` when it is
+    /// missing or does not parse.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.get("/users/:id", |req, res| match req.param_as::<u64>("id") {
+    ///     Some(id) => res.send(&format!("User ID: {}", id)),
+    ///     None => res.status(400).send("User ID must be a number"),
+    /// });
+    /// ```
+    /// ---
+    /// ## Test
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let mut req = Request::new("GET /users/42 HTTP/1.1", HashMap::new(), "".into());
+    /// req.params.insert("id".into(), "42".into());
+    /// assert_eq!(req.param_as::<u64>("id"), Some(42));
+    /// assert_eq!(req.param_as::<bool>("id"), None);
+    /// ```
+    pub fn param_as<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.params.get(key).and_then(|val| val.parse().ok())
+    }
+
+    /// Parses a route parameter into any [`FromStr`] type, or returns an error
+    /// message naming the parameter if it is missing or does not parse.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let mut req = Request::new("GET /users/abc HTTP/1.1", HashMap::new(), "".into());
+    /// req.params.insert("id".into(), "abc".into());
+    /// assert!(req.param_parse::<u32>("id").unwrap_err().contains("must be a valid u32"));
+    /// assert_eq!(req.param_parse::<String>("id").unwrap(), "abc");
+    /// ```
+    pub fn param_parse<T: FromStr>(&self, key: &str) -> Result<T, String> {
+        let val = self.param_expect(key)?;
+        val.parse().map_err(|_| {
+            format!(
+                "[rxpress error]: Route parameter `{}` must be a valid {}, got `{}`.",
+                key,
+                std::any::type_name::<T>(),
+                val
+            )
+        })
+    }
+
+    /// Extracts every route parameter into a struct at once; see [`FromParams`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::{FromParams, Request, Server};
+    ///
+    /// struct PostRef {
+    ///     id: u32,
+    ///     pid: u32,
+    /// }
+    ///
+    /// impl FromParams for PostRef {
+    ///     fn from_params(req: &Request) -> Result<Self, String> {
+    ///         Ok(PostRef {
+    ///             id: req.param_parse("id")?,
+    ///             pid: req.param_parse("pid")?,
+    ///         })
+    ///     }
+    /// }
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.get("/users/:id/posts/:pid", |req, res| {
+    ///     match req.params_struct::<PostRef>() {
+    ///         Ok(post) => res.send(&format!("Post {} of user {}", post.pid, post.id)),
+    ///         Err(err) => res.status(400).send(&err),
+    ///     }
+    /// });
+    /// ```
+    pub fn params_struct<T: FromParams>(&self) -> Result<T, String> {
+        T::from_params(self)
+    }
+
+    /// Gets every segment captured by a repeating route parameter (`:name+`).
+    ///
+    /// Each segment is decoded on its own, so an escaped `%2F` stays inside its
+    /// segment (`/tags/a%2Fb/c` -> `["a/b", "c"]`). Ordinary parameters yield a
+    /// single value, values set by hand are split on `/`, and missing ones give
+    /// an empty list.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.get("/tags/:tag+", |req, res| {
+    ///     res.send(&req.param_all("tag").join(", "));
+    /// });
+    /// ```
+    /// ---
+    /// ## Test
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let mut req = Request::new("GET /tags/rust/web HTTP/1.1", HashMap::new(), "".into());
+    /// req.params.insert("tag".into(), "rust/web".into());
+    ///
+    /// assert_eq!(req.param_all("tag"), vec!["rust", "web"]);
+    /// assert!(req.param_all("missing").is_empty());
+    /// ```
+    pub fn param_all(&self, key: &str) -> Vec<String> {
+        if let Some(segments) = self.param_segments.get(key) {
+            return segments.clone();
+        }
+        match self.params.get(key) {
+            Some(val) => val.split('/').map(|s| s.to_string()).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Gets the parameter names declared by the matched route, in order, e.g.
+    /// to check that validation covers all of them. Empty before routing.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.get("/users/:id/posts/:pid", |req, res| {
+    ///     let missing: Vec<&str> = req
+    ///         .param_names()
+    ///         .into_iter()
+    ///         .filter(|name| req.param(name).is_none())
+    ///         .collect();
+    ///     res.send(&format!("missing: {:?}", missing));
+    /// });
+    /// ```
+    pub fn param_names(&self) -> Vec<&str> {
+        self.param_names.iter().map(|name| name.as_str()).collect()
+    }
+
+    /// Records the decoded segments captured by the repeating parameter `key`;
+    /// `None` forgets segments left by an earlier match.
+    pub(crate) fn set_param_segments(&mut self, key: &str, segments: Option<Vec<String>>) {
+        match segments {
+            Some(segments) => self.param_segments.insert(key.to_string(), segments),
+            None => self.param_segments.remove(key),
+        };
+    }
+
+    /// Records the parameter names of the route that matched this request.
+    pub(crate) fn set_param_names(&mut self, names: Vec<String>) {
+        self.param_names = names;
+    }
+
+    /// Gets a query parameter value. A repeated key keeps its first value; use
+    /// [`Request::query_all`] to read every one.
     ///
     /// # Example
     /// ```no_run
@@ -433,35 +912,879 @@ impl Request {
         ))
     }
 
-    /*---- Private Functions ----*/
-    /// Parses query parameters into a [`HashMap`].
-    fn parse_query(q: &str) -> HashMap<String, String> {
-        let mut map: HashMap<String, String> = HashMap::new();
-
-        for pair in q.split('&') {
-            if let Some((k, v)) = pair.split_once('=') {
-                map.insert(k.to_string(), v.to_string());
-            } else {
-                map.insert(pair.to_string(), "".to_string());
-            }
-        }
-
-        map
+    /// Parses a query parameter into any [`FromStr`] type; `None` when it is
+    /// missing or does not parse.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.get("/items", |req, res| match req.query_as::<u32>("limit") {
+    ///     Some(limit) if limit > 100 => res.status(400).send("limit is at most 100"),
+    ///     _ => res.send("items"),
+    /// });
+    /// ```
+    /// ---
+    /// ## Test
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let req = Request::new("GET /items?limit=20&sort=x HTTP/1.1", HashMap::new(), "".into());
+    /// assert_eq!(req.query_as::<u32>("limit"), Some(20));
+    /// assert_eq!(req.query_as::<u32>("sort"), None);
+    /// ```
+    pub fn query_as<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.query.get(key).and_then(|val| val.parse().ok())
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::collections::HashMap;
 
-    fn make_req_line(line: &str) -> Request {
-        Request::new(line, HashMap::new(), "".into())
+    /// Parses a query parameter like [`Request::query_as`], falling back to
+    /// `default` when it is missing or does not parse.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.get("/posts", |req, res| {
+    ///     let page: usize = req.query_as_or("page", 1);
+    ///     res.send(&format!("Page {}", page));
+    /// });
+    /// ```
+    /// ---
+    /// ## Test
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let req = Request::new("GET /posts?page=abc HTTP/1.1", HashMap::new(), "".into());
+    /// assert_eq!(req.query_as_or("page", 1), 1);
+    /// assert_eq!(req.query_as_or("per_page", 20), 20);
+    /// ```
+    pub fn query_as_or<T: FromStr>(&self, key: &str, default: T) -> T {
+        self.query_as(key).unwrap_or(default)
     }
 
-    // TEST - header test
-    #[test]
-    fn test_header_case_insensitive() {
+    /// Iterates over the query parameters in the order they were sent,
+    /// including repeated keys.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let req = Request::new("GET /sign?b=2&a=1 HTTP/1.1", HashMap::new(), "".into());
+    /// let canonical: Vec<String> = req.query_pairs().map(|(k, v)| format!("{}={}", k, v)).collect();
+    /// assert_eq!(canonical, vec!["b=2", "a=1"]);
+    /// ```
+    pub fn query_pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.query_pairs
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Gets every value of a repeated query parameter, in the order sent
+    /// (e.g. `?tag=a&tag=b`). Values are URL-decoded.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let req = Request::new("GET /posts?tag=a&page=2&tag=b HTTP/1.1", HashMap::new(), "".into());
+    /// assert_eq!(req.query_all("tag"), vec!["a", "b"]);
+    /// assert!(req.query_all("missing").is_empty());
+    /// ```
+    pub fn query_all(&self, key: &str) -> Vec<&String> {
+        self.query_pairs
+            .iter()
+            .filter(|(k, _)| k == key)
+            .map(|(_, v)| v)
+            .collect()
+    }
+
+    /// Gets a cookie sent in the `Cookie` header.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.get("/me", |req, res| {
+    ///     match req.cookie("session") {
+    ///         Some(session) => res.send(&format!("Session: {}", session)),
+    ///         None => res.status(401).send("Not logged in"),
+    ///     }
+    /// });
+    /// ```
+    /// ---
+    /// ## Test
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let mut headers = HashMap::new();
+    /// headers.insert("cookie".to_string(), "theme=dark; session=abc".to_string());
+    /// let req = Request::new("GET / HTTP/1.1", headers, "".into());
+    /// assert_eq!(req.cookie("session"), Some(&"abc".to_string()));
+    /// ```
+    pub fn cookie(&self, name: &str) -> Option<&String> {
+        self.cookies.get(name)
+    }
+
+    /// Gets a cookie or returns a default if missing.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let req = Request::new("GET / HTTP/1.1", HashMap::new(), "".into());
+    /// assert_eq!(req.cookie_or("theme", "light"), "light");
+    /// ```
+    pub fn cookie_or<'a>(&'a self, name: &str, default: &'a str) -> &'a str {
+        self.cookies
+            .get(name)
+            .map(|val| val.as_str())
+            .unwrap_or(default)
+    }
+
+    /// Gets a cookie or returns an error message if missing.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let mut headers = HashMap::new();
+    /// headers.insert("cookie".to_string(), "session=abc".to_string());
+    /// let req = Request::new("GET / HTTP/1.1", headers, "".into());
+    /// assert_eq!(req.cookie_expect("session").unwrap(), "abc");
+    /// assert!(req.cookie_expect("theme").is_err());
+    /// ```
+    pub fn cookie_expect(&self, name: &str) -> Result<&str, String> {
+        self.cookies
+            .get(name)
+            .map(|val| val.as_str())
+            .ok_or(format!(
+                "[rxpress error]: Required cookie `{}` is missing. \
+            Please include it in your request, e.g., `Cookie: {}=value`.",
+                name, name
+            ))
+    }
+
+    /// Parses an `application/x-www-form-urlencoded` body into key-value pairs.
+    ///
+    /// Keys and values are URL-decoded (`%XX` escapes, `+` as space). Like
+    /// [`Request::query`], a repeated key keeps its first value; use
+    /// [`Request::form_all`] to get every one.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.post("/signup", |req, res| {
+    ///     let form = req.form();
+    ///     match form.get("email") {
+    ///         Some(email) => res.send(&format!("Welcome, {}", email)),
+    ///         None => res.status(400).send("Missing email"),
+    ///     }
+    /// });
+    /// ```
+    /// ---
+    /// ## Test
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let body = "name=Ana+Lee&email=ana%40example.com".to_string();
+    /// let req = Request::new("POST /signup HTTP/1.1", HashMap::new(), body);
+    /// let form = req.form();
+    /// assert_eq!(form.get("name"), Some(&"Ana Lee".to_string()));
+    /// assert_eq!(form.get("email"), Some(&"ana@example.com".to_string()));
+    /// ```
+    pub fn form(&self) -> HashMap<String, String> {
+        if self.body.is_empty() {
+            return HashMap::new();
+        }
+
+        let mut form = HashMap::new();
+        for (key, value) in Self::parse_query(self.body.trim_end()) {
+            form.entry(key).or_insert(value);
+        }
+        form
+    }
+
+    /// Gets a single field from an `application/x-www-form-urlencoded` body,
+    /// URL-decoded like [`Request::form`].
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let req = Request::new("POST /search HTTP/1.1", HashMap::new(), "q=caf%C3%A9+au+lait".into());
+    /// assert_eq!(req.form_field("q"), Some("café au lait".to_string()));
+    /// assert_eq!(req.form_field("page"), None);
+    /// ```
+    pub fn form_field(&self, key: &str) -> Option<String> {
+        self.form().remove(key)
+    }
+
+    /// Gets every value of a repeated field in an `application/x-www-form-urlencoded`
+    /// body, in the order sent (e.g. a group of checkboxes). Values are URL-decoded.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.post("/preferences", |req, res| {
+    ///     let tags = req.form_all("tag");
+    ///     res.send(&format!("Selected: {}", tags.join(", ")));
+    /// });
+    /// ```
+    /// ---
+    /// ## Test
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let req = Request::new("POST /prefs HTTP/1.1", HashMap::new(), "tag=a&x=1&tag=b".into());
+    /// assert_eq!(req.form_all("tag"), vec!["a", "b"]);
+    /// assert!(req.form_all("missing").is_empty());
+    /// ```
+    pub fn form_all(&self, key: &str) -> Vec<String> {
+        if self.body.is_empty() {
+            return Vec::new();
+        }
+
+        Self::parse_query(self.body.trim_end())
+            .into_iter()
+            .filter(|(k, _)| k == key)
+            .map(|(_, v)| v)
+            .collect()
+    }
+
+    /// Checks whether the body is declared as JSON: a `Content-Type` of
+    /// `application/json` or any `+json` type, parameters ignored.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let mut headers = HashMap::new();
+    /// headers.insert("content-type".to_string(), "application/json; charset=utf-8".to_string());
+    /// let req = Request::new("POST / HTTP/1.1", headers, "{}".into());
+    /// assert!(req.is_json());
+    /// ```
+    pub fn is_json(&self) -> bool {
+        self.header("Content-Type").is_some_and(|content_type| {
+            let media = content_type
+                .split(';')
+                .next()
+                .unwrap_or("")
+                .trim()
+                .to_ascii_lowercase();
+            media == "application/json" || (media.contains('/') && media.ends_with("+json"))
+        })
+    }
+
+    /// Parses the body as a [`Json`] value.
+    ///
+    /// Fails with a descriptive message when the `Content-Type` is not JSON (see
+    /// [`Request::is_json`]) or the body is malformed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.post("/users", |req, res| {
+    ///     match req.json() {
+    ///         Ok(user) => res.status(201).json(&user.to_string()),
+    ///         Err(err) => res.status(400).send(&err),
+    ///     }
+    /// });
+    /// ```
+    /// ---
+    /// ## Test
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::{Json, Request};
+    ///
+    /// let mut headers = HashMap::new();
+    /// headers.insert("content-type".to_string(), "application/json".to_string());
+    /// let req = Request::new("POST /users HTTP/1.1", headers, r#"{"name":"ana"}"#.into());
+    /// assert_eq!(req.json().unwrap(), Json::Object(vec![("name".into(), Json::String("ana".into()))]));
+    /// ```
+    pub fn json(&self) -> Result<Json, String> {
+        if !self.is_json() {
+            return Err(format!(
+                "[rxpress error]: Expected a JSON body, but `Content-Type` is `{}`. \
+                Please send it with `Content-Type: application/json`.",
+                self.header("Content-Type")
+                    .map_or("missing", |c| c.as_str())
+            ));
+        }
+        Json::parse(&self.body)
+            .map_err(|err| format!("[rxpress error]: Invalid JSON body: {}", err))
+    }
+
+    /// Parses a newline-delimited JSON (NDJSON) body.
+    ///
+    /// Yields one [`Json`] value (or a parse error) per non-empty line of the body.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.post("/ingest", |req, res| {
+    ///     let ok = req.json_lines().filter(|line| line.is_ok()).count();
+    ///     res.send(&format!("Ingested {} records", ok));
+    /// });
+    /// ```
+    /// ---
+    /// ## Test
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::Request;
+    ///
+    /// let body = "{\"id\":1}\n{\"id\":2}\n".to_string();
+    /// let req = Request::new("POST /ingest HTTP/1.1", HashMap::new(), body);
+    /// assert_eq!(req.json_lines().count(), 2);
+    /// ```
+    pub fn json_lines(&self) -> impl Iterator<Item = Result<Json, String>> + '_ {
+        self.body
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(Json::parse)
+    }
+
+    /// Applies the body as an RFC 7386 JSON Merge Patch to `current`.
+    ///
+    /// Returns the patched value, or an error if the body isn't valid JSON.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::{Json, Server};
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.patch("/settings", |req, res| {
+    ///     let current = Json::parse(r#"{"theme":"dark","beta":true}"#).unwrap();
+    ///     match req.merge_patch(&current) {
+    ///         Ok(updated) => res.json(&updated.to_string()),
+    ///         Err(err) => res.status(400).send(&err),
+    ///     }
+    /// });
+    /// ```
+    /// ---
+    /// ## Test
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::{Json, Request};
+    ///
+    /// let body = r#"{"a":null,"b":2}"#.to_string();
+    /// let req = Request::new("PATCH /x HTTP/1.1", HashMap::new(), body);
+    /// let current = Json::parse(r#"{"a":1,"b":1,"c":3}"#).unwrap();
+    ///
+    /// assert_eq!(req.merge_patch(&current).unwrap().to_string(), r#"{"b":2,"c":3}"#);
+    /// ```
+    pub fn merge_patch(&self, current: &Json) -> Result<Json, String> {
+        let patch = Json::parse(&self.body)?;
+        let mut merged = current.clone();
+        merged.merge_patch(&patch);
+        Ok(merged)
+    }
+
+    /// Checks whether the client's cached copy is still fresh, like Express's `req.fresh`.
+    ///
+    /// Compares `If-None-Match` against the response's `ETag` and
+    /// `If-Modified-Since` against its `Last-Modified`. Only `GET`/`HEAD` requests
+    /// with a `2xx` or `304` status can be fresh, and `Cache-Control: no-cache`
+    /// always forces a stale result. Set the validators on the response before
+    /// calling this, then answer `304` when it returns `true`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.get("/report", |req, res| {
+    ///     res.set_header("ETag", "\"v42\"");
+    ///     if req.is_fresh(res) {
+    ///         res.not_modified();
+    ///     } else {
+    ///         res.send("full report");
+    ///     }
+    /// });
+    /// ```
+    pub fn is_fresh(&self, res: &Response) -> bool {
+        let method = self.method.to_ascii_uppercase();
+        let status = res.status_code();
+        if (method != "GET" && method != "HEAD") || !((200..300).contains(&status) || status == 304)
+        {
+            return false;
+        }
+
+        let modified_since = self.header("If-Modified-Since");
+        let none_match = self.header("If-None-Match");
+        if modified_since.is_none() && none_match.is_none() {
+            return false;
+        }
+
+        if self
+            .header("Cache-Control")
+            .is_some_and(|cc| cc.to_ascii_lowercase().contains("no-cache"))
+        {
+            return false;
+        }
+
+        if let Some(none_match) = none_match
+            && none_match.trim() != "*"
+        {
+            let etag = match res.get_header("ETag") {
+                Some(etag) => etag.trim(),
+                None => return false,
+            };
+            // weak comparison: `W/"x"` matches `"x"`
+            let strip_weak = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+            if !none_match
+                .split(',')
+                .any(|tag| strip_weak(tag) == strip_weak(etag))
+            {
+                return false;
+            }
+        }
+
+        if let Some(modified_since) = modified_since {
+            let last_modified = res
+                .get_header("Last-Modified")
+                .and_then(|lm| Self::parse_http_date(lm));
+            match (last_modified, Self::parse_http_date(modified_since)) {
+                (Some(last), Some(since)) if last <= since => {}
+                _ => return false,
+            }
+        }
+
+        true
+    }
+
+    /// Returns a [`Read`] implementation over the request body.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::io::Read;
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.post("/upload", |req, res| {
+    ///     let mut body = req.body_reader();
+    ///     let mut chunk = [0; 1024];
+    ///     let mut total = 0;
+    ///     while let Ok(n) = body.read(&mut chunk) {
+    ///         if n == 0 {
+    ///             break;
+    ///         }
+    ///         total += n;
+    ///         println!("{} bytes left", body.remaining().unwrap_or(0));
+    ///     }
+    ///     res.send(&format!("Read {} bytes", total));
+    /// });
+    /// ```
+    pub fn body_reader(&self) -> BodyReader<'_> {
+        let chunked = self
+            .header("Transfer-Encoding")
+            .is_some_and(|te| te.to_ascii_lowercase().contains("chunked"));
+
+        BodyReader {
+            data: self.body.as_bytes(),
+            sized: !chunked,
+        }
+    }
+
+    /// Defers the body to `reader`: `body` stays empty until
+    /// [`Request::buffer_body`] reads the stream into it. Handy for requests built
+    /// outside the server, whose body is still arriving.
+    pub fn set_body_stream(&mut self, reader: impl Read + Send + 'static) {
+        self.body.clear();
+        self.body_stream = Some(Box::new(reader));
+        self.body_too_large = false;
+    }
+
+    /// Sets the most bytes [`Request::buffer_body`] reads from a body stream.
+    pub(crate) fn set_body_limit(&mut self, limit: usize) {
+        self.body_limit = limit;
+    }
+
+    /// Makes sure the whole body is in memory and returns it, so middleware can
+    /// read it (e.g. to verify a signature) and the handler can still parse it.
+    ///
+    /// A body stream (see [`Request::set_body_stream`]) is drained into `body`
+    /// the first time, within `max_body_bytes` of the server's
+    /// [`RequestLimits`]; a larger body fails with `413`, and a read error with
+    /// `400`. Later calls return the buffered body, and every
+    /// [`Request::body_reader`] starts from its first byte again. Bodies the
+    /// server read off the connection are already in memory.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use std::io::Read;
+    /// use rxpress::Request;
+    ///
+    /// let mut req = Request::new("POST / HTTP/1.1", HashMap::new(), String::new());
+    /// req.set_body_stream("payload".as_bytes());
+    /// assert_eq!(req.buffer_body().unwrap(), "payload");
+    ///
+    /// let mut first = String::new();
+    /// req.body_reader().read_to_string(&mut first).unwrap();
+    /// assert_eq!(first, req.buffer_body().unwrap());
+    /// ```
+    pub fn buffer_body(&mut self) -> Result<&str, RxError> {
+        let too_large = || {
+            RxError::new(
+                413,
+                format!(
+                    "[rxpress error]: Request body exceeds {} bytes.",
+                    self.body_limit
+                ),
+            )
+        };
+        if self.body_too_large {
+            return Err(too_large());
+        }
+
+        if let Some(stream) = self.body_stream.take() {
+            let mut bytes = Vec::new();
+            stream
+                .take(self.body_limit as u64 + 1)
+                .read_to_end(&mut bytes)
+                .map_err(|err| {
+                    RxError::new(
+                        400,
+                        format!("[rxpress error]: Failed to read request body: {}.", err),
+                    )
+                })?;
+            if bytes.len() > self.body_limit {
+                self.body_too_large = true;
+                return Err(too_large());
+            }
+            self.body = String::from_utf8_lossy(&bytes).to_string();
+        }
+        Ok(&self.body)
+    }
+
+    /// Returns `true` if the body was cut off at the size limit, so `body` holds
+    /// only its first bytes.
+    ///
+    /// Only happens when the server truncates oversized bodies instead of
+    /// answering `413` (see
+    /// [`Server::body_overflow`](crate::Server::body_overflow)).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    /// use rxpress::parser::BodyOverflow;
+    ///
+    /// let mut app = Server::new("8080");
+    /// app.max_body_size(1024);
+    /// app.body_overflow(BodyOverflow::Truncate);
+    /// app.post("/notes", |req, res| {
+    ///     if req.body_truncated() {
+    ///         res.status(413).send("Notes are limited to 1 KiB");
+    ///     } else {
+    ///         res.status(201).send("Saved");
+    ///     }
+    /// });
+    /// ```
+    pub fn body_truncated(&self) -> bool {
+        self.body_truncated
+    }
+
+    /// Marks the body as cut off at the size limit.
+    pub(crate) fn set_body_truncated(&mut self, truncated: bool) {
+        self.body_truncated = truncated;
+    }
+
+    /// Returns the server address the connection arrived on, e.g. to tell
+    /// which listener (see [`Server::add_listener`](crate::Server::add_listener))
+    /// served the request.
+    ///
+    /// `None` for requests built by hand or served over a unix socket.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    /// app.add_listener(9090);
+    /// app.get("/", |req, res| match req.local_addr() {
+    ///     Some(addr) if addr.port() == 9090 => res.send("admin"),
+    ///     _ => res.send("public"),
+    /// });
+    /// ```
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    /// Returns the client's address (IP and remote port).
+    ///
+    /// `None` for requests built by hand or served over a unix socket.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    /// app.get("/", |req, res| {
+    ///     let ip = req.peer_addr().map(|addr| addr.ip().to_string());
+    ///     res.send(&ip.unwrap_or_default());
+    /// });
+    /// ```
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.peer_addr
+    }
+
+    /// Returns the query string as sent (undecoded, without the `?`).
+    pub(crate) fn raw_query(&self) -> Option<&str> {
+        self.raw_query.as_deref()
+    }
+
+    /// Records the two ends of the connection the request came in on.
+    pub(crate) fn set_addrs(&mut self, local: Option<SocketAddr>, peer: Option<SocketAddr>) {
+        self.local_addr = local;
+        self.peer_addr = peer;
+    }
+
+    /// Returns a signal that becomes ready when the server shuts down (see
+    /// [`Server::run_until`](crate::Server::run_until)), so streaming handlers
+    /// can break out of their loops.
+    ///
+    /// Requests built by hand, or served by a server not started with
+    /// `run_until`, get a signal that never fires.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use std::time::Duration;
+    /// use rxpress::Request;
+    ///
+    /// let req = Request::new("GET /events HTTP/1.1", HashMap::new(), "".into());
+    /// let shutdown = req.shutdown_signal();
+    /// assert!(!shutdown.is_ready());
+    /// assert!(!shutdown.wait_timeout(Duration::from_millis(1)));
+    /// ```
+    pub fn shutdown_signal(&self) -> ShutdownSignal {
+        self.shutdown.clone()
+    }
+
+    /// Shares the server's shutdown signal with this request.
+    pub(crate) fn set_shutdown_signal(&mut self, signal: &ShutdownSignal) {
+        self.shutdown = signal.clone();
+    }
+
+    /*---- Private Functions ----*/
+    /// Checks whether a media range (`text/*`, `*/*`, `text/html`) covers a MIME type.
+    fn media_matches(range: &str, mime: &str) -> bool {
+        if range == "*/*" || range.eq_ignore_ascii_case(mime) {
+            return true;
+        }
+
+        match (range.split_once('/'), mime.split_once('/')) {
+            (Some((r_type, "*")), Some((m_type, _))) => r_type.eq_ignore_ascii_case(m_type),
+            _ => false,
+        }
+    }
+
+    /// Parses an IMF-fixdate (`Sun, 06 Nov 1994 08:49:37 GMT`) into Unix seconds.
+    fn parse_http_date(date: &str) -> Option<i64> {
+        let parts: Vec<&str> = date.split_whitespace().collect();
+        let [_, day, month, year, time, "GMT"] = parts.as_slice() else {
+            return None;
+        };
+
+        let day: i64 = day.parse().ok()?;
+        let year: i64 = year.parse().ok()?;
+        let month = [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ]
+        .iter()
+        .position(|m| m == month)? as i64
+            + 1;
+
+        let mut hms = time.split(':').map(|p| p.parse::<i64>());
+        let (h, m, sec) = match (hms.next(), hms.next(), hms.next(), hms.next()) {
+            (Some(Ok(h)), Some(Ok(m)), Some(Ok(sec)), None) => (h, m, sec),
+            _ => return None,
+        };
+
+        // days since 1970-01-01 (Howard Hinnant's days_from_civil)
+        let y = if month <= 2 { year - 1 } else { year };
+        let era = y.div_euclid(400);
+        let yoe = y - era * 400;
+        let mp = (month + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = era * 146097 + doe - 719468;
+
+        Some(days * 86400 + h * 3600 + m * 60 + sec)
+    }
+
+    /// Splits an absolute-form target into its authority and origin-form target.
+    fn split_absolute_form(target: &str) -> Option<(String, String)> {
+        let (scheme, rest) = target.split_once("://")?;
+        if !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https") {
+            return None;
+        }
+
+        let end = rest.find(['/', '?']).unwrap_or(rest.len());
+        let (host, path) = rest.split_at(end);
+        let path = if path.starts_with('/') {
+            path.to_string()
+        } else {
+            format!("/{}", path)
+        };
+
+        Some((host.to_string(), path))
+    }
+
+    /// Parses a `Cookie` header (`a=1; b=2`); the first of duplicate names wins.
+    fn parse_cookies(header: &str) -> HashMap<String, String> {
+        let mut cookies = HashMap::new();
+        for pair in header.split(';') {
+            // split on the first `=` only, values may contain more
+            if let Some((name, value)) = pair.split_once('=') {
+                let name = name.trim();
+                if !name.is_empty() {
+                    cookies
+                        .entry(name.to_string())
+                        .or_insert_with(|| value.trim().to_string());
+                }
+            }
+        }
+        cookies
+    }
+
+    /// Parses query parameters into URL-decoded key-value pairs, keeping their order.
+    fn parse_query(q: &str) -> Vec<(String, String)> {
+        let mut pairs = Vec::new();
+
+        for pair in q.split('&') {
+            let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+            pairs.push((parser::decode_component(k), parser::decode_component(v)));
+        }
+
+        pairs
+    }
+}
+
+/// Types built from a request's route parameters, for
+/// [`Request::params_struct`].
+///
+/// Implementations usually fill each field with [`Request::param_parse`], so a
+/// missing or malformed parameter is reported by name.
+pub trait FromParams: Sized {
+    /// Builds `Self` from the route parameters of `req`.
+    fn from_params(req: &Request) -> Result<Self, String>;
+}
+
+/// A reader over the request body.
+///
+/// Created with [`Request::body_reader`].
+pub struct BodyReader<'a> {
+    data: &'a [u8],
+    sized: bool,
+}
+
+impl BodyReader<'_> {
+    /// Bytes left to read, or `None` when the length isn't known up front
+    /// (`Transfer-Encoding: chunked`).
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use std::io::Read;
+    /// use rxpress::Request;
+    ///
+    /// let req = Request::new("POST / HTTP/1.1", HashMap::new(), "hello".into());
+    /// let mut body = req.body_reader();
+    /// body.read_exact(&mut [0; 2]).unwrap();
+    /// assert_eq!(body.remaining(), Some(3));
+    /// ```
+    pub fn remaining(&self) -> Option<u64> {
+        self.sized.then_some(self.data.len() as u64)
+    }
+}
+
+impl Read for BodyReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.data.read(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn make_req_line(line: &str) -> Request {
+        Request::new(line, HashMap::new(), "".into())
+    }
+
+    // TEST - absolute-form targets route on their path
+    #[test]
+    fn test_absolute_form_target() {
+        let mut headers = HashMap::new();
+        headers.insert("host".into(), "proxy.local".into());
+        let req = Request::new(
+            "GET http://example.com/users/5?x=1 HTTP/1.1",
+            headers,
+            "".into(),
+        );
+        assert_eq!(req.path, "/users/5");
+        assert_eq!(req.query("x"), Some(&"1".to_string()));
+        assert_eq!(req.header("Host"), Some(&"example.com".to_string()));
+
+        let req = make_req_line("GET https://example.com:8443?q=a HTTP/1.1");
+        assert_eq!(req.path, "/");
+        assert_eq!(req.query("q"), Some(&"a".to_string()));
+        assert_eq!(req.header("host"), Some(&"example.com:8443".to_string()));
+    }
+
+    // TEST - version parsing
+    #[test]
+    fn test_http_version() {
+        assert_eq!(
+            make_req_line("GET / HTTP/1.0").http_version(),
+            &HttpVersion::Http10
+        );
+        assert_eq!(
+            make_req_line("GET / HTTP/1.1").http_version(),
+            &HttpVersion::Http11
+        );
+        let req = make_req_line("GET / HTTP/2");
+        assert_eq!(req.http_version(), &HttpVersion::Other("HTTP/2".into()));
+        assert_eq!(req.http_version().to_string(), "HTTP/2");
+    }
+
+    // TEST - header test
+    #[test]
+    fn test_header_case_insensitive() {
         let mut headers = HashMap::new();
         headers.insert("Content-Type".into(), "application/json".into());
         let req = Request::new("GET / HTTP/1.1", headers, "".into());
@@ -501,6 +1824,75 @@ mod tests {
         assert!(err.contains("Required header `X-Token` is missing"));
     }
 
+    #[test]
+    fn test_auth_scheme() {
+        let mut headers = HashMap::new();
+        headers.insert("authorization".into(), "Bearer abc".into());
+        let req = Request::new("GET / HTTP/1.1", headers, "".into());
+        assert_eq!(req.auth_scheme(), Some(("Bearer", "abc")));
+
+        let mut headers = HashMap::new();
+        headers.insert("Authorization".into(), "Token xyz".into());
+        let req = Request::new("GET / HTTP/1.1", headers, "".into());
+        assert_eq!(req.auth_scheme(), Some(("Token", "xyz")));
+
+        let req = make_req_line("GET / HTTP/1.1");
+        assert_eq!(req.auth_scheme(), None);
+    }
+
+    // TEST - header values compare case-insensitively, as a whole
+    #[test]
+    fn test_header_eq_ignore_case() {
+        let mut headers = HashMap::new();
+        headers.insert("Connection".to_string(), "Keep-Alive".to_string());
+        let req = Request::new("GET / HTTP/1.1", headers, "".into());
+
+        assert!(req.header_eq_ignore_case("connection", "keep-alive"));
+        assert!(req.header_eq_ignore_case("CONNECTION", "KEEP-ALIVE"));
+        assert!(!req.header_eq_ignore_case("connection", "keep"));
+        assert!(!req.header_eq_ignore_case("upgrade", "keep-alive"));
+    }
+
+    #[test]
+    fn test_is_ajax() {
+        let mut headers = HashMap::new();
+        headers.insert("x-requested-with".into(), "XMLHttpRequest".into());
+        let req = Request::new("GET / HTTP/1.1", headers, "".into());
+        assert!(req.is_ajax());
+
+        assert!(!make_req_line("GET / HTTP/1.1").is_ajax());
+    }
+
+    #[test]
+    fn test_accepts() {
+        let mut headers = HashMap::new();
+        headers.insert(
+            "accept".into(),
+            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8".into(),
+        );
+        let browser = Request::new("GET / HTTP/1.1", headers, "".into());
+        assert_eq!(
+            browser.accepts(&["text/plain", "application/json"]),
+            Some("text/plain")
+        );
+        assert_eq!(
+            browser.accepts(&["application/json", "text/html"]),
+            Some("text/html")
+        );
+
+        let mut headers = HashMap::new();
+        headers.insert("accept".into(), "application/json".into());
+        let api = Request::new("GET / HTTP/1.1", headers, "".into());
+        assert_eq!(
+            api.accepts(&["text/plain", "application/json"]),
+            Some("application/json")
+        );
+        assert_eq!(api.accepts(&["text/plain"]), None);
+
+        let no_header = make_req_line("GET / HTTP/1.1");
+        assert_eq!(no_header.accepts(&["text/plain"]), Some("text/plain"));
+    }
+
     //TEST - params test
     #[test]
     fn test_param_insertion_and_lookup() {
@@ -531,6 +1923,39 @@ mod tests {
         assert!(err.contains("Required route parameter `username` is missing"));
     }
 
+    // TEST - route params extracted into a struct
+    #[test]
+    fn test_params_struct() {
+        #[derive(Debug)]
+        struct PostRef {
+            id: u32,
+            pid: u32,
+        }
+
+        impl FromParams for PostRef {
+            fn from_params(req: &Request) -> Result<Self, String> {
+                Ok(PostRef {
+                    id: req.param_parse("id")?,
+                    pid: req.param_parse("pid")?,
+                })
+            }
+        }
+
+        let mut req = make_req_line("GET /users/7/posts/42 HTTP/1.1");
+        req.params.insert("id".into(), "7".into());
+        req.params.insert("pid".into(), "42".into());
+        let post = req.params_struct::<PostRef>().unwrap();
+        assert_eq!((post.id, post.pid), (7, 42));
+
+        req.params.insert("pid".into(), "latest".into());
+        let err = req.params_struct::<PostRef>().unwrap_err();
+        assert!(err.contains("Route parameter `pid` must be a valid u32, got `latest`"));
+
+        req.params.remove("id");
+        let err = req.params_struct::<PostRef>().unwrap_err();
+        assert!(err.contains("Required route parameter `id` is missing"));
+    }
+
     //TEST - query test
     #[test]
     fn test_query_lookup() {
@@ -558,12 +1983,225 @@ mod tests {
         assert!(err.contains("Required query parameter `page` is missing"));
     }
 
+    // TEST - cookies: `=` inside values, first duplicate wins
+    #[test]
+    fn test_cookies() {
+        let mut headers = HashMap::new();
+        headers.insert(
+            "Cookie".to_string(),
+            " token=a=b==; theme=dark ;theme=light; flag; =orphan".to_string(),
+        );
+        let req = Request::new("GET / HTTP/1.1", headers, "".into());
+
+        assert_eq!(req.cookie("token"), Some(&"a=b==".to_string()));
+        assert_eq!(req.cookie("theme"), Some(&"dark".to_string()));
+        assert_eq!(req.cookie("flag"), None);
+        assert_eq!(req.cookie_or("lang", "en"), "en");
+
+        let err = req.cookie_expect("session").unwrap_err();
+        assert!(err.contains("Required cookie `session` is missing"));
+    }
+
+    // TEST - JSON bodies need a JSON content type and valid syntax
+    #[test]
+    fn test_json_body() {
+        let with_type = |content_type: &str, body: &str| {
+            let mut headers = HashMap::new();
+            headers.insert("content-type".to_string(), content_type.to_string());
+            Request::new("POST / HTTP/1.1", headers, body.into())
+        };
+
+        let req = with_type("application/vnd.api+json", "[1, 2]");
+        assert!(req.is_json());
+        assert_eq!(req.json().unwrap().to_string(), "[1,2]");
+
+        let err = with_type("text/plain", "[1, 2]").json().unwrap_err();
+        assert!(err.contains("`Content-Type` is `text/plain`"));
+        let err = make_req_line("POST / HTTP/1.1").json().unwrap_err();
+        assert!(err.contains("`Content-Type` is `missing`"));
+
+        let err = with_type("application/json", "{\"a\":").json().unwrap_err();
+        assert!(err.starts_with("[rxpress error]: Invalid JSON body"));
+    }
+
+    // TEST - form bodies are URL-decoded, malformed escapes kept
+    #[test]
+    fn test_form_decoding() {
+        let body = "a%20b=1%2B1&note=100%&bad=%zz&tag=x&tag=y+z\r\n";
+        let req = Request::new("POST / HTTP/1.1", HashMap::new(), body.into());
+
+        let form = req.form();
+        assert_eq!(form.get("a b"), Some(&"1+1".to_string()));
+        assert_eq!(form.get("note"), Some(&"100%".to_string()));
+        assert_eq!(form.get("bad"), Some(&"%zz".to_string()));
+        assert_eq!(req.form_field("tag"), Some("x".to_string()));
+        assert_eq!(req.form_all("tag"), vec!["x", "y z"]);
+        assert!(make_req_line("POST / HTTP/1.1").form().is_empty());
+    }
+
+    // TEST - query strings are decoded, `+` included
+    #[test]
+    fn test_query_decoding() {
+        let req =
+            make_req_line("GET /search?q=hello%20world&tag=c%2B%2B&name=Ana+Lee&bad=%ZZ HTTP/1.1");
+        assert_eq!(req.query("q"), Some(&"hello world".to_string()));
+        assert_eq!(req.query("tag"), Some(&"c++".to_string()));
+        assert_eq!(req.query("name"), Some(&"Ana Lee".to_string()));
+        assert_eq!(req.query("bad"), Some(&"%ZZ".to_string()));
+    }
+
+    // TEST - typed query and param extraction
+    #[test]
+    fn test_typed_extraction() {
+        let mut req =
+            make_req_line("GET /users/7?page=3&ratio=0.5&debug=true&page_size=-1 HTTP/1.1");
+        req.params.insert("id".into(), "7".into());
+
+        assert_eq!(req.param_as::<u32>("id"), Some(7));
+        assert_eq!(req.param_as::<u32>("missing"), None);
+        assert_eq!(req.query_as::<usize>("page"), Some(3));
+        assert_eq!(req.query_as::<f64>("ratio"), Some(0.5));
+        assert_eq!(req.query_as::<bool>("debug"), Some(true));
+        assert_eq!(req.query_as::<usize>("page_size"), None);
+        assert_eq!(req.query_as_or::<usize>("page_size", 25), 25);
+        assert_eq!(req.query_as_or("page", 1_usize), 3);
+    }
+
+    // TEST - repeated query keys are all kept, `query` still sees one value
+    #[test]
+    fn test_query_all() {
+        let req = make_req_line("GET /posts?tag=rust&tag=c%2B%2B&page=1 HTTP/1.1");
+        assert_eq!(req.query_all("tag"), vec!["rust", "c++"]);
+        assert_eq!(req.query_all("page"), vec!["1"]);
+        assert!(req.query_all("none").is_empty());
+        assert_eq!(req.query("tag"), Some(&"rust".to_string()));
+    }
+
+    //TEST - NDJSON body
+    #[test]
+    fn test_json_lines() {
+        let body = "{\"id\":1}\n{\"id\":\n\n{\"id\":3}\n".to_string();
+        let req = Request::new("POST /ingest HTTP/1.1", HashMap::new(), body);
+
+        let results: Vec<_> = req.json_lines().collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap().to_string(), r#"{"id":3}"#);
+    }
+
+    //TEST - cache freshness
+    fn fresh_against(req_headers: &[(&str, &str)], res_headers: &[(&str, &str)]) -> bool {
+        let headers = req_headers
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let req = Request::new("GET /report HTTP/1.1", headers, "".into());
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let _client = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let mut stream = listener.accept().unwrap().0;
+        let mut res = Response::new(&mut stream);
+        for (k, v) in res_headers {
+            res.set_header(k, v);
+        }
+        req.is_fresh(&res)
+    }
+
+    #[test]
+    fn test_is_fresh_etag() {
+        let etag = [("ETag", "\"v1\"")];
+        assert!(fresh_against(&[("if-none-match", "\"v1\"")], &etag));
+        assert!(fresh_against(
+            &[("if-none-match", "\"v0\", W/\"v1\"")],
+            &etag
+        ));
+        assert!(!fresh_against(&[("if-none-match", "\"v2\"")], &etag));
+        assert!(!fresh_against(
+            &[("if-none-match", "\"v1\""), ("cache-control", "no-cache")],
+            &etag
+        ));
+        assert!(!fresh_against(&[], &etag));
+    }
+
+    #[test]
+    fn test_is_fresh_last_modified() {
+        let last_modified = [("Last-Modified", "Sun, 06 Nov 1994 08:49:37 GMT")];
+        assert!(fresh_against(
+            &[("if-modified-since", "Sun, 06 Nov 1994 08:49:37 GMT")],
+            &last_modified
+        ));
+        assert!(!fresh_against(
+            &[("if-modified-since", "Sat, 05 Nov 1994 08:49:37 GMT")],
+            &last_modified
+        ));
+        assert_eq!(
+            Request::parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(784111777)
+        );
+    }
+
+    //TEST - body reader
+    #[test]
+    fn test_body_reader_remaining() {
+        let mut headers = HashMap::new();
+        headers.insert("content-length".into(), "10".into());
+        let req = Request::new("POST / HTTP/1.1", headers, "0123456789".into());
+
+        let mut body = req.body_reader();
+        assert_eq!(body.remaining(), Some(10));
+        let mut buf = [0; 4];
+        body.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"0123");
+        assert_eq!(body.remaining(), Some(6));
+        let mut rest = String::new();
+        body.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "456789");
+        assert_eq!(body.remaining(), Some(0));
+
+        let mut headers = HashMap::new();
+        headers.insert("transfer-encoding".into(), "chunked".into());
+        let req = Request::new("POST / HTTP/1.1", headers, "abc".into());
+        assert_eq!(req.body_reader().remaining(), None);
+    }
+
     //TEST - query parser(Private Method)
     #[test]
     fn test_parse_query_function() {
         let parsed = Request::parse_query("a=1&b=2&empty");
-        assert_eq!(parsed.get("a"), Some(&"1".to_string()));
-        assert_eq!(parsed.get("b"), Some(&"2".to_string()));
-        assert_eq!(parsed.get("empty"), Some(&"".to_string()));
+        assert_eq!(
+            parsed,
+            vec![
+                ("a".to_string(), "1".to_string()),
+                ("b".to_string(), "2".to_string()),
+                ("empty".to_string(), "".to_string()),
+            ]
+        );
+    }
+
+    // TEST - repeated form fields
+    #[test]
+    fn test_form_all_repeated_fields() {
+        let req = Request::new(
+            "POST /prefs HTTP/1.1",
+            HashMap::new(),
+            "tag=rust&name=x&tag=web&tag=\r\n".into(),
+        );
+        assert_eq!(req.form_all("tag"), vec!["rust", "web", ""]);
+        assert_eq!(req.form_all("name"), vec!["x"]);
+    }
+
+    // TEST - query pairs keep their original order
+    #[test]
+    fn test_query_pairs_order() {
+        let req = make_req_line("GET /x?a=1&b=2&c=3 HTTP/1.1");
+        let pairs: Vec<(&str, &str)> = req.query_pairs().collect();
+        assert_eq!(pairs, vec![("a", "1"), ("b", "2"), ("c", "3")]);
+        assert!(
+            make_req_line("GET /x HTTP/1.1")
+                .query_pairs()
+                .next()
+                .is_none()
+        );
     }
 }
@@ -18,40 +18,210 @@
 //! ```
 
 use std::collections::HashMap;
-use std::fs;
-use std::io::Write;
-use std::net::TcpStream;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
 
-use crate::status::{HttpStatus, StatusArg};
+use crate::cookie::{CookieBuilder, CookieJar};
+use crate::error::RxError;
+use crate::json::Json;
+use crate::mime;
+use crate::request::{HttpVersion, Request};
+use crate::status::{HttpStatus, StatusArg, StatusClass};
+
+/// Hook run on a [`Response`] right before it is written to the client.
+pub type ResponseHook = fn(&mut Response);
+
+/// Rewrites a buffered response body given the request and its `Content-Type`.
+pub type BodyTransform = fn(&Request, &[u8], &str) -> Vec<u8>;
+
+/// Server-wide settings applied to every [`Response`].
+pub(crate) struct ResponseOptions {
+    /// Append `\n` to `send()` bodies that don't already end with one
+    pub(crate) trailing_newline: bool,
+    /// Reason phrases overriding the [`HttpStatus::reason`] defaults
+    pub(crate) status_reasons: Option<HashMap<u16, String>>,
+    /// Include internal details (e.g. file paths) in error bodies
+    pub(crate) debug_errors: bool,
+    /// Hooks run before writing responses of a given status class
+    pub(crate) status_hooks: Vec<(StatusClass, ResponseHook)>,
+    /// `Content-Language` sent when the handler doesn't set one
+    pub(crate) default_language: Option<String>,
+    /// Advertise `Accept-Ranges: bytes` on file responses
+    pub(crate) accept_ranges: bool,
+    /// Transformer applied to buffered bodies before they are written
+    pub(crate) body_transform: Option<BodyTransform>,
+    /// Smallest buffered body that is gzipped for clients accepting it
+    #[cfg(feature = "gzip")]
+    pub(crate) compress_min: Option<usize>,
+}
+
+impl ResponseOptions {
+    pub(crate) const fn new() -> ResponseOptions {
+        ResponseOptions {
+            trailing_newline: false,
+            status_reasons: None,
+            debug_errors: cfg!(debug_assertions),
+            status_hooks: Vec::new(),
+            default_language: None,
+            accept_ranges: true,
+            body_transform: None,
+            #[cfg(feature = "gzip")]
+            compress_min: None,
+        }
+    }
+}
+
+static DEFAULT_OPTIONS: ResponseOptions = ResponseOptions::new();
+
+/// Size of the buffer used when streaming bodies with [`Response::send_reader`].
+const CHUNK_SIZE: usize = 64 * 1024;
 
 /// Represents an HTTP response.
 ///
 /// Used by route handlers to set status codes, headers, and send body content.
+///
+/// Setters such as [`Response::with_status`] and [`Response::with_header`]
+/// return `&mut Self` and chain; body methods (`send`, `json`, `html`, and the
+/// other senders) end the chain. They write the response and mark it sent, so
+/// any later body call is ignored with a warning.
+///
+/// # Example
+/// ```
+/// use rxpress::Response;
+///
+/// let mut out: Vec<u8> = Vec::new();
+/// let mut res = Response::new(&mut out);
+/// res.with_status(201).with_header("X-Id", "5").json(r#"{"id":5}"#);
+/// assert!(res.is_sent());
+///
+/// let out = String::from_utf8(out).unwrap();
+/// assert!(out.starts_with("HTTP/1.1 201 Created\r\n"));
+/// assert!(out.contains("X-Id: 5\r\n"));
+/// ```
 pub struct Response<'a> {
-    stream: &'a mut TcpStream,
-    headers: HashMap<String, String>,
+    stream: &'a mut dyn Write,
+    headers: Vec<(String, String)>,
+    /// Cookies to set, one `Set-Cookie` line each
+    cookies: CookieJar,
     status: HttpStatus,
     status_code: u16,
     status_reason: String,
+    custom_reason: bool,
+    version: HttpVersion,
+    options: &'a ResponseOptions,
+    bytes_written: usize,
     sent: bool,
+    /// Hold buffered bodies back until [`Response::flush_deferred`]
+    defer: bool,
+    deferred: Option<Vec<u8>>,
+    /// Answering a `HEAD` request: write the head, never the body
+    omit_body: bool,
+    /// The body is being streamed with chunked framing
+    chunked: bool,
+    /// A write to the client failed (it left, or the write timeout ran out)
+    write_failed: bool,
+    /// The request's `Accept-Encoding` allows gzip
+    #[cfg(feature = "gzip")]
+    accept_gzip: bool,
 }
 
 impl<'a> Response<'a> {
     /// Creates a new Response with default `200 OK`.
-    pub fn new(stream: &'a mut TcpStream) -> Response<'a> {
-        let mut headers = HashMap::new();
-        headers.insert("HTTP-Server-Powered-By".to_string(), "rxpress".to_string());
+    ///
+    /// The response is written to any [`Write`] sink, e.g. a `TcpStream`,
+    /// a `UnixStream`, or an in-memory buffer.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Response;
+    ///
+    /// let mut out: Vec<u8> = Vec::new();
+    /// Response::new(&mut out).send("hi");
+    ///
+    /// assert!(String::from_utf8(out).unwrap().ends_with("\r\n\r\nhi"));
+    /// ```
+    pub fn new(stream: &'a mut dyn Write) -> Response<'a> {
+        let headers = vec![("HTTP-Server-Powered-By".to_string(), "rxpress".to_string())];
 
         Response {
             stream,
             headers,
+            cookies: CookieJar::new(),
             status: HttpStatus::OK,
             status_code: 200,
             status_reason: "OK".to_string(),
+            custom_reason: false,
+            version: HttpVersion::Http11,
+            options: &DEFAULT_OPTIONS,
+            bytes_written: 0,
             sent: false,
+            defer: false,
+            deferred: None,
+            omit_body: false,
+            chunked: false,
+            write_failed: false,
+            #[cfg(feature = "gzip")]
+            accept_gzip: false,
         }
     }
 
+    /// Applies the server-wide response settings.
+    pub(crate) fn set_options(&mut self, options: &'a ResponseOptions) -> &mut Self {
+        self.options = options;
+        self
+    }
+
+    /// Buffers the body of the next send instead of writing it right away, so the
+    /// server can run the body transformer once the handler is done.
+    pub(crate) fn defer_body(&mut self) -> &mut Self {
+        self.defer = true;
+        self
+    }
+
+    /// Records whether the client accepts gzip-encoded bodies.
+    #[cfg(feature = "gzip")]
+    pub(crate) fn set_accept_gzip(&mut self, accepted: bool) -> &mut Self {
+        self.accept_gzip = accepted;
+        self
+    }
+
+    /// Leaves the body out of whatever is sent next, keeping its `Content-Length`
+    /// (for `HEAD` requests).
+    pub(crate) fn omit_body(&mut self) -> &mut Self {
+        self.omit_body = true;
+        self
+    }
+
+    /// Writes a deferred body, passing it through the body transformer first.
+    pub(crate) fn flush_deferred(&mut self, req: &Request) {
+        self.defer = false;
+        let body = match self.deferred.take() {
+            Some(body) => body,
+            None => return,
+        };
+
+        let body = match self.options.body_transform {
+            Some(transform) => {
+                let content_type = self.get_header("Content-Type").map_or("", |c| c.as_str());
+                transform(req, &body, content_type)
+            }
+            None => body,
+        };
+        self.write_response(&body);
+    }
+
+    /// Sets the HTTP version used in the status line.
+    ///
+    /// Only `HTTP/1.0` is honoured; any other version answers with `HTTP/1.1`.
+    /// HTTP/1.0 responses default to `Connection: close`.
+    pub(crate) fn set_version(&mut self, version: &HttpVersion) -> &mut Self {
+        self.version = match version {
+            HttpVersion::Http10 => HttpVersion::Http10,
+            _ => HttpVersion::Http11,
+        };
+        self
+    }
+
     /// Sets the HTTP status.
     ///
     /// Supports `HttpStatus` enum, numeric codes, or custom code + reason.
@@ -95,22 +265,62 @@ impl<'a> Response<'a> {
                 self.status = e;
                 self.status_code = e.code();
                 self.status_reason = HttpStatus::reason(self.status_code).to_string();
+                self.custom_reason = false;
             }
             StatusArg::Code(code) => {
                 self.status_code = code;
                 self.status_reason = HttpStatus::reason(code).to_string();
+                self.custom_reason = false;
             }
             StatusArg::CodeReason(code, reason) => {
                 self.status_code = code;
                 self.status_reason = reason.to_string();
+                self.custom_reason = true;
             }
         }
 
         self
     }
 
+    /// Sets the status from an [`HttpStatus`] variant.
+    ///
+    /// Same as [`Response::status`] with an enum, spelled out for clarity.
+    ///
+    /// # Example
+    /// ```
+    /// # use rxpress::{HttpStatus, Response};
+    /// # fn handler(res: &mut Response) {
+    /// res.status_enum(HttpStatus::ImATeapot).send("🫖");
+    /// # }
+    /// ```
+    pub fn status_enum(&mut self, status: HttpStatus) -> &mut Self {
+        self.status(status)
+    }
+
+    /// Sets the status and returns the response for further chaining; same as
+    /// [`Response::status`], named to read as a builder step.
+    ///
+    /// # Example
+    /// ```
+    /// # use rxpress::Response;
+    /// # fn handler(res: &mut Response) {
+    /// res.with_status(202).with_header("Retry-After", "5").send("Queued");
+    /// # }
+    /// ```
+    pub fn with_status<'b, T: Into<StatusArg<'b>>>(&mut self, arg: T) -> &mut Self {
+        self.status(arg)
+    }
+
     /// Sets a header on the response.
     ///
+    /// Header names must be valid RFC 7230 tokens. Invalid names (e.g. `X Custom`)
+    /// print a warning and are ignored, so a malformed response is never emitted.
+    /// Values containing CR, LF, or NUL would split the response and are ignored
+    /// the same way.
+    ///
+    /// Headers are written in the order they were first set; setting one again
+    /// (case-insensitive) replaces its value in place.
+    ///
     /// # Example
     /// ```
     /// # use rxpress::{Response};
@@ -119,12 +329,160 @@ impl<'a> Response<'a> {
     /// # }
     /// ```
     pub fn set_header(&mut self, key: &str, value: &str) -> &mut Self {
-        self.headers.insert(key.to_string(), value.to_string());
+        if !Self::is_token(key) {
+            eprintln!(
+                "[rxpress warning!]: invalid header name `{}`, ignoring set_header() call.",
+                key
+            );
+            return self;
+        }
+        if value.contains(['\r', '\n', '\0']) {
+            eprintln!(
+                "[rxpress warning!]: value of header `{}` contains CR, LF, or NUL, ignoring set_header() call.",
+                key
+            );
+            return self;
+        }
+        // replace in place so headers keep the order they were first set in
+        match self
+            .headers
+            .iter_mut()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        {
+            Some(header) => *header = (key.to_string(), value.to_string()),
+            None => self.headers.push((key.to_string(), value.to_string())),
+        }
+        self
+    }
+
+    /// Sets a header and returns the response for further chaining; same as
+    /// [`Response::set_header`], named to read as a builder step.
+    ///
+    /// # Example
+    /// ```
+    /// # use rxpress::Response;
+    /// # fn handler(res: &mut Response) {
+    /// res.with_header("Cache-Control", "no-store").with_status(200).json("[]");
+    /// # }
+    /// ```
+    pub fn with_header(&mut self, key: &str, value: &str) -> &mut Self {
+        self.set_header(key, value)
+    }
+
+    /// Adds a `Set-Cookie` header for `name=value`, with no attributes.
+    ///
+    /// Every call adds its own `Set-Cookie` line, so several cookies can be set
+    /// on one response. Use [`Response::set_cookie`] for `Path`, `Max-Age`, etc.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Response;
+    ///
+    /// let mut out: Vec<u8> = Vec::new();
+    /// Response::new(&mut out).cookie("a", "1").cookie("b", "2").end();
+    ///
+    /// let out = String::from_utf8(out).unwrap();
+    /// assert!(out.contains("Set-Cookie: a=1\r\nSet-Cookie: b=2\r\n"));
+    /// ```
+    pub fn cookie(&mut self, name: &str, value: &str) -> &mut Self {
+        self.set_cookie(&CookieBuilder::new(name, value))
+    }
+
+    /// Adds a `Set-Cookie` header built with a [`CookieBuilder`].
+    ///
+    /// Same as [`CookieJar::add`] on [`Response::cookies_mut`]; invalid cookies,
+    /// including a `Path` with control characters or `;`, print a warning and
+    /// are ignored.
+    ///
+    /// # Example
+    /// ```
+    /// # use rxpress::Response;
+    /// use rxpress::cookie::CookieBuilder;
+    /// # fn handler(res: &mut Response) {
+    /// res.set_cookie(&CookieBuilder::new("session", "abc").path("/").http_only());
+    /// # }
+    /// ```
+    pub fn set_cookie(&mut self, cookie: &CookieBuilder) -> &mut Self {
+        self.cookies.add(cookie.clone());
+        self
+    }
+
+    /// Gives access to the cookies this response sets, to add or remove several
+    /// with their own attributes.
+    ///
+    /// # Example
+    /// ```
+    /// # use rxpress::Response;
+    /// use rxpress::cookie::CookieBuilder;
+    /// # fn handler(res: &mut Response) {
+    /// res.cookies_mut()
+    ///     .add(CookieBuilder::new("a", "1").path("/"))
+    ///     .remove("old");
+    /// # }
+    /// ```
+    pub fn cookies_mut(&mut self) -> &mut CookieJar {
+        &mut self.cookies
+    }
+
+    /// Removes a header set earlier (case-insensitive), e.g. from a status-class
+    /// hook registered with [`Server::on_status_class`](crate::Server::on_status_class).
+    ///
+    /// # Example
+    /// ```
+    /// # use rxpress::Response;
+    /// # fn handler(res: &mut Response) {
+    /// res.set_header("Server", "rxpress");
+    /// res.remove_header("server");
+    /// assert_eq!(res.get_header("Server"), None);
+    /// # }
+    /// ```
+    pub fn remove_header(&mut self, key: &str) -> &mut Self {
+        self.headers.retain(|(k, _)| !k.eq_ignore_ascii_case(key));
         self
     }
 
+    /// Gets a header already set on the response (case-insensitive).
+    ///
+    /// # Example
+    /// ```
+    /// # use rxpress::Response;
+    /// # fn handler(res: &mut Response) {
+    /// res.set_header("ETag", "\"v1\"");
+    /// assert_eq!(res.get_header("etag"), Some(&"\"v1\"".to_string()));
+    /// # }
+    /// ```
+    pub fn get_header(&self, key: &str) -> Option<&String> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v)
+    }
+
+    /// Sets the `Content-Language` header, overriding
+    /// [`Server::default_language`](crate::Server::default_language).
+    ///
+    /// # Example
+    /// ```
+    /// # use rxpress::Response;
+    /// # fn handler(res: &mut Response) {
+    /// res.content_language("de-DE").send("Hallo Welt");
+    /// # }
+    /// ```
+    pub fn content_language(&mut self, lang: &str) -> &mut Self {
+        self.set_header("Content-Language", lang)
+    }
+
+    /// Returns the status code that will be (or was) sent.
+    pub fn status_code(&self) -> u16 {
+        self.status_code
+    }
+
     /// Sends a plain text response with `Content-Type: text/plain`.
     ///
+    /// When [`Server::text_trailing_newline`](crate::Server::text_trailing_newline)
+    /// is enabled, a `\n` is appended to bodies that don't already end with one.
+    /// An empty body stays empty and is sent with `Content-Length: 0`.
+    ///
     /// # Example
     /// ```
     /// # use rxpress::{Response};
@@ -142,7 +500,12 @@ impl<'a> Response<'a> {
         }
         self.sent = true; // mark as sent
         self.set_header("Content-Type", "text/plain");
-        self.write_response(msg.as_bytes());
+        // keep empty bodies empty so `Content-Length: 0` frames them exactly
+        if self.options.trailing_newline && !msg.is_empty() && !msg.ends_with('\n') {
+            self.write_response(format!("{}\n", msg).as_bytes());
+        } else {
+            self.write_response(msg.as_bytes());
+        }
     }
 
     /// Sends a JSON response with `Content-Type: application/json`.
@@ -167,6 +530,58 @@ impl<'a> Response<'a> {
         self.write_response(msg.as_bytes());
     }
 
+    /// Like [`Response::json`], but first checks that `msg` is well-formed JSON.
+    ///
+    /// Malformed input returns the parse error and leaves the response unsent, so
+    /// the handler can still send something else.
+    ///
+    /// # Example
+    /// ```
+    /// # use rxpress::Response;
+    /// # fn handler(res: &mut Response) {
+    /// if let Err(err) = res.json_checked(r#"{"message": "ok""#) {
+    ///     res.status(500).send(&err);
+    /// }
+    /// # }
+    /// ```
+    pub fn json_checked(&mut self, msg: &str) -> Result<(), String> {
+        Json::parse(msg)?;
+        self.json(msg);
+        Ok(())
+    }
+
+    /// Sends a JSON error in the canonical shape
+    /// `{"error":{"code":<status>,"message":"..."}}` with `status` as the status
+    /// code. The message is escaped as a JSON string.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Response;
+    ///
+    /// let mut out: Vec<u8> = Vec::new();
+    /// Response::new(&mut out).error(404, "No user with id 7");
+    ///
+    /// let out = String::from_utf8(out).unwrap();
+    /// assert!(out.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    /// assert!(out.ends_with(r#"{"error":{"code":404,"message":"No user with id 7"}}"#));
+    /// ```
+    pub fn error(&mut self, status: u16, message: &str) {
+        if self.sent {
+            eprintln!(
+                "[rxpress warning!]: response already sent, ignoring subsequent error() call."
+            );
+            return;
+        }
+        let body = Json::Object(vec![(
+            "error".to_string(),
+            Json::Object(vec![
+                ("code".to_string(), Json::Number(status.into())),
+                ("message".to_string(), Json::String(message.to_string())),
+            ]),
+        )]);
+        self.status(status).json(&body.to_string());
+    }
+
     /// Sends an HTML response with `Content-Type: text/html; charset=utf-8`.
     ///
     /// # Example
@@ -191,8 +606,33 @@ impl<'a> Response<'a> {
         self.write_response(body.as_bytes());
     }
 
+    /// Sends raw bytes with the given `Content-Type`, e.g. an image generated in
+    /// memory.
+    ///
+    /// # Example
+    /// ```
+    /// # use rxpress::Response;
+    /// # fn handler(res: &mut Response) {
+    /// let pixel: &[u8] = &[0x47, 0x49, 0x46, 0x38, 0x39, 0x61];
+    /// res.send_bytes(pixel, "image/gif");
+    /// # }
+    /// ```
+    pub fn send_bytes(&mut self, bytes: &[u8], content_type: &str) {
+        if self.sent {
+            eprintln!(
+                "[rxpress warning!]: response already sent, ignoring subsequent send_bytes() call."
+            );
+            return;
+        }
+        self.sent = true; // mark as sent
+        self.set_header("Content-Type", content_type);
+        self.write_response(bytes);
+    }
+
     /// Sends the contents of an HTML file with `Content-Type: text/html; charset=utf-8`.
-    /// If the file cannot be read, responds with `500 Internal Server Error`.
+    /// If the file cannot be read, responds with `500 Internal Server Error`. The
+    /// error body only names the path when [`Server::debug_errors`](crate::Server::debug_errors)
+    /// is enabled.
     ///
     /// # Example
     /// ```no_run
@@ -215,74 +655,1021 @@ impl<'a> Response<'a> {
         self.set_header("Content-Type", "text/html; charset=utf-8");
         match fs::read_to_string(path) {
             Ok(content) => {
+                self.advertise_ranges();
                 self.write_response(content.as_bytes());
             }
             Err(_) => {
-                let body = &format!(
-                    "<h2>Internal Server Error</h2>\n<p>No file found on {}</p>",
-                    path
-                );
+                let body = &if self.options.debug_errors {
+                    format!(
+                        "<h2>Internal Server Error</h2>\n<p>No file found on {}</p>",
+                        path
+                    )
+                } else {
+                    "<h2>Internal Server Error</h2>".to_string()
+                };
                 self.status(HttpStatus::InternalServerError);
                 self.write_response(body.as_bytes());
             }
         }
     }
 
-    /*---- Private Functions ----*/
-    /// Send header & response message
-    fn write_response(&mut self, msg: &[u8]) {
-        let headers = self
-            .headers
-            .iter()
-            .map(|(k, v)| format!("{}: {}", k, v))
-            .collect::<Vec<String>>()
-            .join("\r\n");
-
-        // println!("[write_response]: {headers:?}");
-        let res = format!(
-            "HTTP/1.1 {} {}\r\n{}\r\nContent-Length: {}\r\n\r\n",
-            self.status_code,
-            self.status_reason,
-            headers,
-            msg.len()
-        );
+    /// Streams `len` bytes from `reader` as the body, in fixed-size chunks.
+    ///
+    /// The body is never held in memory as a whole, which makes this suitable for
+    /// large downloads. Returns an error if writing fails or `reader` ends or
+    /// fails before `len` bytes were sent. Each chunk is checked, so a client that disconnects
+    /// or stops reading for longer than
+    /// [`Server::write_timeout`](crate::Server::write_timeout) ends the stream
+    /// early; the server then closes the connection.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use rxpress::Response;
+    /// # fn handler(res: &mut Response) {
+    /// let data: &[u8] = b"streamed body";
+    /// res.send_reader(data, data.len() as u64, "text/plain").unwrap();
+    /// # }
+    /// ```
+    pub fn send_reader<R: Read>(
+        &mut self,
+        mut reader: R,
+        len: u64,
+        content_type: &str,
+    ) -> io::Result<()> {
+        if self.sent {
+            eprintln!(
+                "[rxpress warning!]: response already sent, ignoring subsequent send_reader() call."
+            );
+            return Ok(());
+        }
+        self.sent = true; // mark as sent
+        self.set_header("Content-Type", content_type);
 
-        self.stream.write_all(res.as_bytes()).unwrap();
-        self.stream.write_all(msg).unwrap();
-        self.stream.flush().unwrap();
-    }
-}
+        let head = self.head(Some(len));
+        let result = self.stream.write_all(head.as_bytes());
+        self.track(result)?;
+        if self.omit_body {
+            let result = self.stream.flush();
+            return self.track(result);
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::net::{TcpListener, TcpStream};
+        let mut buffer = vec![0; CHUNK_SIZE];
+        let mut remaining = len;
+        while remaining > 0 {
+            let want = remaining.min(CHUNK_SIZE as u64) as usize;
+            let read = match reader.read(&mut buffer[..want]) {
+                Ok(read) => read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    // the head is out, so the body is cut short
+                    self.write_failed = true;
+                    return Err(err);
+                }
+            };
+            if read == 0 {
+                // the promised length can no longer be met
+                self.write_failed = true;
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "body reader ended before the declared length",
+                ));
+            }
+            let result = self.stream.write_all(&buffer[..read]);
+            self.track(result)?;
+            self.bytes_written += read;
+            remaining -= read as u64;
+        }
 
-    // helper to get a connected TcpStream pair
-    fn tcp_pair() -> (TcpStream, TcpStream) {
-        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
-        let addr = listener.local_addr().unwrap();
+        let result = self.stream.flush();
+        self.track(result)
+    }
 
-        let client = TcpStream::connect(addr).unwrap();
-        let server = listener.accept().unwrap().0;
+    /// Streams a file from disk, guessing its `Content-Type` from the extension.
+    ///
+    /// The file is sent in chunks with its size as `Content-Length`, so even very
+    /// large files are never loaded into memory. Missing or unreadable files get a
+    /// `404 Not Found`.
+    ///
+    /// Like [`Response::html_file`], this advertises `Accept-Ranges: bytes` unless
+    /// the handler set its own value (e.g. `none`) or
+    /// [`Server::accept_ranges`](crate::Server::accept_ranges) turned it off.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.get("/download", |_, res| {
+    ///     res.send_file("assets/archive.zip");
+    /// });
+    /// ```
+    pub fn send_file(&mut self, path: &str) {
+        if self.sent {
+            eprintln!(
+                "[rxpress warning!]: response already sent, ignoring subsequent send_file() call."
+            );
+            return;
+        }
 
-        (client, server)
-    }
+        let opened = File::open(path).and_then(|file| {
+            let meta = file.metadata()?;
+            if meta.is_file() {
+                Ok((file, meta.len()))
+            } else {
+                Err(io::Error::other("not a regular file"))
+            }
+        });
 
-    // TEST - set custom header
-    #[test]
-    fn test_set_header() {
-        let (_c, mut s) = tcp_pair();
-        let mut res = Response::new(&mut s);
-        res.set_header("X-Test", "123");
-        assert_eq!(res.headers.get("X-Test"), Some(&"123".to_string()));
+        match opened {
+            Ok((file, len)) => {
+                self.advertise_ranges();
+                if let Err(err) = self.send_reader(file, len, mime::from_path(path)) {
+                    eprintln!("[rxpress error]: failed to stream file: {}", err);
+                }
+            }
+            Err(_) => {
+                self.status(HttpStatus::NotFound).send("404 Not Found");
+            }
+        }
     }
 
-    // TEST - sent status from HttpStatus enum
-    #[test]
-    fn test_status_with_enum() {
-        let (_c, mut s) = tcp_pair();
-        let mut res = Response::new(&mut s);
+    /// Sends a file from disk as raw bytes, guessing its `Content-Type` from the
+    /// extension and setting `Content-Length`.
+    ///
+    /// Unlike [`Response::html_file`], the contents need not be UTF-8, so images
+    /// and other binary files work. The file is read into memory first; prefer
+    /// [`Response::send_file`] for large downloads. Missing files and directories
+    /// get a `404 Not Found`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.get("/logo", |_, res| {
+    ///     res.file("assets/logo.png");
+    /// });
+    /// ```
+    pub fn file(&mut self, path: &str) {
+        if self.sent {
+            eprintln!(
+                "[rxpress warning!]: response already sent, ignoring subsequent file() call."
+            );
+            return;
+        }
+
+        match fs::read(path) {
+            Ok(content) => {
+                self.advertise_ranges();
+                self.send_bytes(&content, mime::from_path(path));
+            }
+            Err(_) => {
+                self.status(HttpStatus::NotFound).send("404 Not Found");
+            }
+        }
+    }
+
+    /// Ends the response with an empty body, keeping the status and headers set so far.
+    ///
+    /// # Example
+    /// ```
+    /// # use rxpress::Response;
+    /// # fn handler(res: &mut Response) {
+    /// res.status(204).end();
+    /// # }
+    /// ```
+    pub fn end(&mut self) {
+        if self.sent {
+            eprintln!("[rxpress warning!]: response already sent, ignoring subsequent end() call.");
+            return;
+        }
+        self.sent = true; // mark as sent
+        self.write_response(b"");
+    }
+
+    /// Answers `304 Not Modified`, telling the client its cached copy is still
+    /// good.
+    ///
+    /// No body or `Content-Length` is sent and any `Content-Type` is dropped;
+    /// caching headers set so far (`ETag`, `Cache-Control`, `Last-Modified`,
+    /// ...) are kept. Pairs with [`Request::is_fresh`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.get("/logo", |req, res| {
+    ///     res.set_header("ETag", "\"v3\"");
+    ///     if req.header("If-None-Match").is_some_and(|tag| tag == "\"v3\"") {
+    ///         return res.not_modified();
+    ///     }
+    ///     res.file("assets/logo.png");
+    /// });
+    /// ```
+    pub fn not_modified(&mut self) {
+        if self.sent {
+            eprintln!(
+                "[rxpress warning!]: response already sent, ignoring subsequent not_modified() call."
+            );
+            return;
+        }
+        self.remove_header("Content-Type");
+        self.status(HttpStatus::NotModified).end();
+    }
+
+    /// Redirects to `location` with `302 Found`.
+    ///
+    /// Sets the `Location` header and a small HTML body linking to the target,
+    /// for clients that don't follow redirects on their own.
+    ///
+    /// # Example
+    /// ```
+    /// # use rxpress::Response;
+    /// # fn handler(res: &mut Response) {
+    /// res.redirect("/login");
+    /// # }
+    /// ```
+    pub fn redirect(&mut self, location: &str) {
+        self.redirect_with(302, location);
+    }
+
+    /// Redirects to `location` with a specific status: `301`, `302`, `303`, `307`,
+    /// or `308`. Any other code falls back to `302` with a warning.
+    ///
+    /// A `location` containing CR, LF, or NUL (e.g. a decoded `?next=%0d%0a...`)
+    /// would split the response, so the call is ignored with a warning.
+    ///
+    /// # Example
+    /// ```
+    /// # use rxpress::Response;
+    /// # fn handler(res: &mut Response) {
+    /// res.redirect_with(301, "https://example.com/new-home");
+    /// # }
+    /// ```
+    pub fn redirect_with(&mut self, code: u16, location: &str) {
+        if self.sent {
+            eprintln!(
+                "[rxpress warning!]: response already sent, ignoring subsequent redirect() call."
+            );
+            return;
+        }
+        if location.contains(['\r', '\n', '\0']) {
+            eprintln!(
+                "[rxpress warning!]: redirect location contains CR, LF, or NUL, ignoring redirect() call."
+            );
+            return;
+        }
+        let code = match code {
+            301 | 302 | 303 | 307 | 308 => code,
+            _ => {
+                eprintln!(
+                    "[rxpress warning!]: {} is not a redirect status, redirecting with 302.",
+                    code
+                );
+                302
+            }
+        };
+
+        let href = location
+            .replace('&', "&amp;")
+            .replace('"', "&quot;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        self.status(code).set_header("Location", location);
+        self.html(&format!("<a href=\"{}\">{}</a>", href, href));
+    }
+
+    /// Writes `bytes` to the connection verbatim, e.g. an upstream response a
+    /// proxy forwards as-is.
+    ///
+    /// No status line, headers, or framing are added, and the status, headers,
+    /// and server-wide options set on this response are ignored. The caller is
+    /// responsible for `bytes` being a complete, valid HTTP response.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Response;
+    ///
+    /// let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nhi";
+    /// let mut out: Vec<u8> = Vec::new();
+    /// Response::new(&mut out).send_raw(raw);
+    ///
+    /// assert_eq!(out, raw);
+    /// ```
+    pub fn send_raw(&mut self, bytes: &[u8]) {
+        if self.sent {
+            eprintln!(
+                "[rxpress warning!]: response already sent, ignoring subsequent send_raw() call."
+            );
+            return;
+        }
+        self.sent = true; // mark as sent
+        let result = self
+            .stream
+            .write_all(bytes)
+            .and_then(|()| self.stream.flush());
+        if self.track(result).is_ok() {
+            self.bytes_written = bytes.len();
+        }
+    }
+
+    /// Streams a JSON array item by item, without buffering the whole result.
+    ///
+    /// Sends `Content-Type: application/json` with chunked framing, writes `[`
+    /// right away, then each [`JsonArrayWriter::push`]ed item, and `]` on
+    /// [`JsonArrayWriter::finish`] or drop. HTTP/1.0 clients get the array
+    /// unframed, followed by closing the connection. The streamed body skips
+    /// [`Server::transform_body`](crate::Server::transform_body).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::{Json, Server};
+    ///
+    /// let mut app = Server::new("8080");
+    ///
+    /// app.get("/numbers", |_req, res| {
+    ///     let mut array = res.json_stream();
+    ///     for n in 0..1000 {
+    ///         if array.push(&Json::Number(n as f64)).is_err() {
+    ///             return; // client went away
+    ///         }
+    ///     }
+    /// });
+    /// ```
+    pub fn json_stream(&mut self) -> JsonArrayWriter<'_, 'a> {
+        let mut open = false;
+        if self.sent {
+            eprintln!(
+                "[rxpress warning!]: response already sent, ignoring subsequent json_stream() call."
+            );
+        } else {
+            self.sent = true; // mark as sent
+            self.set_header("Content-Type", "application/json");
+            match self.start_chunked().and_then(|()| self.write_chunk(b"[")) {
+                Ok(()) => open = true,
+                Err(err) => eprintln!("[rxpress error]: failed to start JSON stream: {}", err),
+            }
+        }
+
+        JsonArrayWriter {
+            res: self,
+            first: true,
+            open,
+        }
+    }
+
+    /// Reports how the body was framed: `chunked` for streamed bodies such as
+    /// [`Response::json_stream`], `identity` for everything sent with a
+    /// `Content-Length` (or an HTTP/1.0 stream delimited by closing).
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Response;
+    ///
+    /// let mut out: Vec<u8> = Vec::new();
+    /// let mut res = Response::new(&mut out);
+    /// res.send("hi");
+    /// assert_eq!(res.transfer_encoding(), "identity");
+    /// ```
+    pub fn transfer_encoding(&self) -> &str {
+        if self.chunked { "chunked" } else { "identity" }
+    }
+
+    /// Returns `true` once a body method (`send()`, `json()`, `end()`, ...) has run.
+    pub fn is_sent(&self) -> bool {
+        self.sent
+    }
+
+    /// Sends a complete response from its parts: status code, headers, and raw body.
+    ///
+    /// Convenient for handlers that compute the whole response up front. The status
+    /// takes the same forms as [`Response::status`]. Like the other body methods,
+    /// only the first call sends anything.
+    ///
+    /// # Example
+    /// ```
+    /// # use rxpress::Response;
+    /// # fn handler(res: &mut Response) {
+    /// res.send_parts(
+    ///     201,
+    ///     &[("Content-Type", "application/json"), ("X-Id", "5")],
+    ///     br#"{"id":5}"#,
+    /// );
+    /// # }
+    /// ```
+    pub fn send_parts<'b, T: Into<StatusArg<'b>>>(
+        &mut self,
+        status: T,
+        headers: &[(&str, &str)],
+        body: &[u8],
+    ) {
+        if self.sent {
+            eprintln!(
+                "[rxpress warning!]: response already sent, ignoring subsequent send_parts() call."
+            );
+            return;
+        }
+        self.sent = true; // mark as sent
+        self.status(status);
+        for (key, value) in headers {
+            self.set_header(key, value);
+        }
+        self.write_response(body);
+    }
+
+    /// Returns the number of body bytes written to the client (`0` before sending).
+    ///
+    /// Useful for access logs, e.g. `200 1234b`.
+    ///
+    /// # Example
+    /// ```
+    /// # use rxpress::Response;
+    /// # fn handler(res: &mut Response) {
+    /// res.send("hello");
+    /// assert_eq!(res.bytes_written(), 5);
+    /// # }
+    /// ```
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written
+    }
+
+    /// Reports whether writing to the client failed, leaving the response cut off.
+    pub(crate) fn write_failed(&self) -> bool {
+        self.write_failed
+    }
+
+    /// Sends an [`RxError`] as a plain text error response.
+    ///
+    /// The error message is only exposed with debug errors on; otherwise the
+    /// body is the standard reason phrase. Ignored if a response was already sent.
+    pub(crate) fn send_error(&mut self, err: &RxError) {
+        if self.sent {
+            eprintln!(
+                "[rxpress error]: handler failed after sending a response: {}",
+                err
+            );
+            return;
+        }
+
+        let body = if self.options.debug_errors {
+            err.message().to_string()
+        } else {
+            HttpStatus::reason(err.status()).to_string()
+        };
+        self.status(err.status()).send(&body);
+    }
+
+    /*---- Private Functions ----*/
+    /// Checks that a header name is a valid RFC 7230 token.
+    pub(crate) fn is_token(name: &str) -> bool {
+        !name.is_empty()
+            && name
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+    }
+
+    /// Builds the status line and headers for a body of `content_length` bytes,
+    /// or a chunked (HTTP/1.0: unframed) body when the length is `None`
+    fn head(&mut self, content_length: Option<u64>) -> String {
+        // status-class hooks may still adjust headers at this point
+        let options = self.options;
+        let class = StatusClass::of(self.status_code);
+        for (hook_class, hook) in &options.status_hooks {
+            if Some(*hook_class) == class {
+                hook(self);
+            }
+        }
+
+        if self.version == HttpVersion::Http10
+            && !self
+                .headers
+                .iter()
+                .any(|(k, _)| k.eq_ignore_ascii_case("Connection"))
+        {
+            self.set_header("Connection", "close");
+        }
+
+        if let Some(lang) = &options.default_language
+            && self.get_header("Content-Language").is_none()
+        {
+            self.set_header("Content-Language", lang);
+        }
+
+        let headers = self
+            .headers
+            .iter()
+            .map(|(k, v)| format!("{}: {}", k, v))
+            .chain(
+                self.cookies
+                    .iter()
+                    .map(|c| format!("Set-Cookie: {}", c.build())),
+            )
+            .collect::<Vec<String>>()
+            .join("\r\n");
+
+        // server-wide reason overrides apply unless the handler chose its own
+        if !self.custom_reason
+            && let Some(reason) = self
+                .options
+                .status_reasons
+                .as_ref()
+                .and_then(|reasons| reasons.get(&self.status_code))
+        {
+            self.status_reason = reason.clone();
+        }
+
+        // println!("[write_response]: {headers:?}");
+        let framing = match content_length {
+            // a 304 stands for the cached body, whose length isn't 0
+            Some(_) if self.status_code == 304 => String::new(),
+            Some(len) => format!("Content-Length: {}\r\n", len),
+            None if self.version != HttpVersion::Http10 => {
+                "Transfer-Encoding: chunked\r\n".to_string()
+            }
+            None => String::new(),
+        };
+        format!(
+            "{} {} {}\r\n{}\r\n{}\r\n",
+            self.version, self.status_code, self.status_reason, headers, framing
+        )
+    }
+
+    /// Writes the head of a streamed body: chunked, or unframed with
+    /// `Connection: close` for HTTP/1.0.
+    fn start_chunked(&mut self) -> io::Result<()> {
+        if self.version == HttpVersion::Http10 {
+            self.set_header("Connection", "close");
+        } else {
+            self.chunked = true;
+        }
+        let head = self.head(None);
+        let result = self.stream.write_all(head.as_bytes());
+        self.track(result)
+    }
+
+    /// Writes one piece of a streamed body.
+    fn write_chunk(&mut self, data: &[u8]) -> io::Result<()> {
+        if self.omit_body || data.is_empty() {
+            return Ok(());
+        }
+        let result = if self.chunked {
+            write!(self.stream, "{:x}\r\n", data.len())
+                .and_then(|()| self.stream.write_all(data))
+                .and_then(|()| self.stream.write_all(b"\r\n"))
+        } else {
+            self.stream.write_all(data)
+        };
+        self.track(result)?;
+        self.bytes_written += data.len();
+        Ok(())
+    }
+
+    /// Terminates a streamed body.
+    fn end_chunked(&mut self) -> io::Result<()> {
+        let mut result = Ok(());
+        if self.chunked && !self.omit_body {
+            result = self.stream.write_all(b"0\r\n\r\n");
+        }
+        let result = result.and_then(|()| self.stream.flush());
+        self.track(result)
+    }
+
+    /// Gzips a buffered body when compression is on, the body is large enough and
+    /// of a compressible type, and the client accepts it, setting
+    /// `Content-Encoding` and `Vary` to match. Returns `None` to send `msg` as is.
+    #[cfg(feature = "gzip")]
+    fn gzip_body(&mut self, msg: &[u8]) -> Option<Vec<u8>> {
+        let min = self.options.compress_min?;
+        let content_type = self.get_header("Content-Type").map_or("", |c| c.as_str());
+        if msg.len() < min
+            || !crate::gzip::is_compressible(content_type)
+            || self.get_header("Content-Encoding").is_some()
+            || self.get_header("Content-Range").is_some()
+            || matches!(self.status_code, 204 | 304)
+        {
+            return None;
+        }
+
+        // caches must keep the encoded and plain variants apart
+        let vary = match self.get_header("Vary") {
+            Some(vary) if vary.to_ascii_lowercase().contains("accept-encoding") => vary.clone(),
+            Some(vary) => format!("{}, Accept-Encoding", vary),
+            None => "Accept-Encoding".to_string(),
+        };
+        self.set_header("Vary", &vary);
+        if !self.accept_gzip {
+            return None;
+        }
+
+        self.set_header("Content-Encoding", "gzip");
+        Some(crate::gzip::compress(msg))
+    }
+
+    /// Remembers a failed write, so the connection is not reused.
+    fn track(&mut self, result: io::Result<()>) -> io::Result<()> {
+        if result.is_err() {
+            self.write_failed = true;
+        }
+        result
+    }
+
+    /// Adds `Accept-Ranges: bytes` for file bodies unless disabled or already set.
+    fn advertise_ranges(&mut self) {
+        if self.options.accept_ranges && self.get_header("Accept-Ranges").is_none() {
+            self.set_header("Accept-Ranges", "bytes");
+        }
+    }
+
+    /// Send header & response message
+    fn write_response(&mut self, msg: &[u8]) {
+        if self.defer {
+            self.deferred = Some(msg.to_vec());
+            return;
+        }
+
+        #[cfg(feature = "gzip")]
+        let compressed = self.gzip_body(msg);
+        #[cfg(feature = "gzip")]
+        let msg = compressed.as_deref().unwrap_or(msg);
+
+        let res = self.head(Some(msg.len() as u64));
+
+        let mut result = self.stream.write_all(res.as_bytes());
+        if !self.omit_body && result.is_ok() {
+            result = self.stream.write_all(msg);
+            if result.is_ok() {
+                self.bytes_written = msg.len();
+            }
+        }
+        let result = result.and_then(|()| self.stream.flush());
+        let _ = self.track(result);
+    }
+}
+
+/// Writes a JSON array to the client one item at a time, created with
+/// [`Response::json_stream`].
+///
+/// The closing `]` is written by [`JsonArrayWriter::finish`], or when the
+/// writer is dropped.
+pub struct JsonArrayWriter<'r, 'a> {
+    res: &'r mut Response<'a>,
+    first: bool,
+    open: bool,
+}
+
+impl JsonArrayWriter<'_, '_> {
+    /// Appends `value` to the array, sending it to the client right away.
+    ///
+    /// Fails once the client has gone away; does nothing if the stream could
+    /// not be started.
+    pub fn push(&mut self, value: &Json) -> io::Result<()> {
+        if !self.open {
+            return Ok(());
+        }
+        let item = if self.first {
+            value.to_string()
+        } else {
+            format!(",{}", value)
+        };
+        self.first = false;
+        self.res.write_chunk(item.as_bytes())
+    }
+
+    /// Closes the array and ends the response.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.close()
+    }
+
+    /*---- Private Functions ----*/
+    fn close(&mut self) -> io::Result<()> {
+        if !self.open {
+            return Ok(());
+        }
+        self.open = false;
+        self.res.write_chunk(b"]")?;
+        self.res.end_chunked()
+    }
+}
+
+impl Drop for JsonArrayWriter<'_, '_> {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::{TcpListener, TcpStream};
+
+    // helper to get a connected TcpStream pair
+    fn tcp_pair() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let client = TcpStream::connect(addr).unwrap();
+        let server = listener.accept().unwrap().0;
+
+        (client, server)
+    }
+
+    // helper to read everything written once the server side is closed
+    fn read_all(mut client: TcpStream) -> String {
+        let mut out = String::new();
+        client.read_to_string(&mut out).unwrap();
+        out
+    }
+
+    // TEST - redirects set the status, Location, and a fallback link
+    #[test]
+    fn test_redirect() {
+        let mut out: Vec<u8> = Vec::new();
+        let mut res = Response::new(&mut out);
+        res.redirect("/login?next=<a>&b");
+        res.redirect("/ignored"); // already sent
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("HTTP/1.1 302 Found\r\n"));
+        assert!(out.contains("Location: /login?next=<a>&b\r\n"));
+        assert!(out.ends_with(
+            "\r\n\r\n<a href=\"/login?next=&lt;a&gt;&amp;b\">/login?next=&lt;a&gt;&amp;b</a>"
+        ));
+
+        let mut out: Vec<u8> = Vec::new();
+        Response::new(&mut out).redirect_with(308, "/new");
+        assert!(
+            String::from_utf8(out)
+                .unwrap()
+                .starts_with("HTTP/1.1 308 Permanent Redirect")
+        );
+
+        let mut out: Vec<u8> = Vec::new();
+        Response::new(&mut out).redirect_with(200, "/new");
+        assert!(
+            String::from_utf8(out)
+                .unwrap()
+                .starts_with("HTTP/1.1 302 Found")
+        );
+    }
+
+    // TEST - a location carrying CRLF can't inject headers
+    #[test]
+    fn test_redirect_rejects_crlf() {
+        let mut out: Vec<u8> = Vec::new();
+        let mut res = Response::new(&mut out);
+        res.redirect("/home\r\nSet-Cookie: session=evil");
+        res.redirect_with(301, "/home\0");
+        assert!(!res.is_sent());
+
+        res.redirect("/home");
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("Location: /home\r\n"));
+        assert!(!out.contains("Set-Cookie"));
+    }
+
+    // TEST - raw responses are written byte for byte
+    #[test]
+    fn test_send_raw() {
+        let raw =
+            b"HTTP/1.1 418 I'm a teapot\r\nX-Upstream: yes\r\nContent-Length: 3\r\n\r\n\x00\xffz";
+        let mut out: Vec<u8> = Vec::new();
+        let mut res = Response::new(&mut out);
+        res.set_header("X-Ignored", "1").status(500);
+        res.send_raw(raw);
+        res.send("ignored"); // already sent
+        assert_eq!(res.bytes_written(), raw.len());
+        assert_eq!(out, raw);
+    }
+
+    // TEST - each cookie gets its own Set-Cookie line, invalid ones are dropped
+    #[test]
+    fn test_cookies() {
+        use crate::cookie::SameSite;
+
+        let mut out: Vec<u8> = Vec::new();
+        Response::new(&mut out)
+            .cookie("theme", "dark")
+            .cookie("bad name", "x")
+            .cookie("bad", "a;b")
+            .set_cookie(&CookieBuilder::new("evil", "1").path("/\r\nEvil: 2"))
+            .set_cookie(&CookieBuilder::new("attr", "1").path("/; Domain=evil.com"))
+            .set_cookie(
+                &CookieBuilder::new("session", "abc")
+                    .max_age(60)
+                    .same_site(SameSite::Strict),
+            )
+            .send("ok");
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains(
+            "Set-Cookie: theme=dark\r\nSet-Cookie: session=abc; Max-Age=60; SameSite=Strict\r\n"
+        ));
+        assert_eq!(out.matches("Set-Cookie").count(), 2);
+        assert!(!out.contains("Evil") && !out.contains("Domain"));
+    }
+
+    // TEST - the jar emits one Set-Cookie per entry, removals with Max-Age=0
+    #[test]
+    fn test_cookie_jar() {
+        let mut out: Vec<u8> = Vec::new();
+        let mut res = Response::new(&mut out);
+        res.cookie("old", "1");
+        res.cookies_mut()
+            .add(CookieBuilder::new("session", "abc").path("/").http_only())
+            .add(CookieBuilder::new("lang", "en").max_age(3600))
+            .remove("old");
+        res.end();
+
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = out
+            .lines()
+            .filter(|l| l.starts_with("Set-Cookie:"))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Set-Cookie: old=; Max-Age=0",
+                "Set-Cookie: session=abc; Path=/; HttpOnly",
+                "Set-Cookie: lang=en; Max-Age=3600",
+            ]
+        );
+    }
+
+    // TEST - a streamed JSON array arrives chunked and reassembles to valid JSON
+    #[test]
+    fn test_json_stream() {
+        let mut out: Vec<u8> = Vec::new();
+        let mut res = Response::new(&mut out);
+        {
+            let mut array = res.json_stream();
+            for id in 1..=3 {
+                let item = Json::Object(vec![("id".to_string(), Json::Number(id as f64))]);
+                array.push(&item).unwrap();
+            }
+        } // dropped -> closed
+        res.send("ignored"); // already sent
+        assert_eq!(res.bytes_written(), r#"[{"id":1},{"id":2},{"id":3}]"#.len());
+
+        let out = String::from_utf8(out).unwrap();
+        let (head, body) = out.split_once("\r\n\r\n").unwrap();
+        assert!(head.contains("Transfer-Encoding: chunked") && !head.contains("Content-Length"));
+        assert!(body.starts_with("1\r\n[\r\n") && body.ends_with("1\r\n]\r\n0\r\n\r\n"));
+
+        let headers = HashMap::from([("transfer-encoding".to_string(), "chunked".to_string())]);
+        let limits = crate::parser::RequestLimits::default();
+        let json = crate::parser::get_body(&mut body.as_bytes(), &headers, &limits).unwrap();
+        assert_eq!(json, r#"[{"id":1},{"id":2},{"id":3}]"#);
+        assert!(Json::parse(&json).is_ok());
+
+        // HTTP/1.0 gets the bare array and a closed connection
+        let mut out: Vec<u8> = Vec::new();
+        let mut res = Response::new(&mut out);
+        res.set_version(&HttpVersion::Http10);
+        res.json_stream().finish().unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("Connection: close\r\n") && out.ends_with("\r\n\r\n[]"));
+    }
+
+    // TEST - files are sent as bytes with a guessed type, missing ones 404
+    #[test]
+    fn test_file() {
+        let path = std::env::temp_dir().join(format!("rxpress-file-{}.png", std::process::id()));
+        fs::write(&path, [0x89, b'P', b'N', b'G', 0xff]).unwrap();
+
+        let mut out: Vec<u8> = Vec::new();
+        Response::new(&mut out).file(path.to_str().unwrap());
+        fs::remove_file(&path).unwrap();
+        assert!(out.starts_with(b"HTTP/1.1 200 OK\r\n"));
+        let head = String::from_utf8_lossy(&out);
+        assert!(head.contains("Content-Type: image/png\r\n"));
+        assert!(head.contains("Content-Length: 5\r\n"));
+        assert!(out.ends_with(&[b'\n', 0x89, b'P', b'N', b'G', 0xff]));
+
+        let mut out: Vec<u8> = Vec::new();
+        Response::new(&mut out).file(path.to_str().unwrap());
+        assert!(out.starts_with(b"HTTP/1.1 404 Not Found\r\n"));
+    }
+
+    // TEST - the framing in use is reported for logging
+    #[test]
+    fn test_transfer_encoding() {
+        let mut out: Vec<u8> = Vec::new();
+        let mut res = Response::new(&mut out);
+        res.json_stream().finish().unwrap();
+        assert_eq!(res.transfer_encoding(), "chunked");
+
+        let mut out: Vec<u8> = Vec::new();
+        let mut res = Response::new(&mut out);
+        assert_eq!(res.transfer_encoding(), "identity");
+        res.send("fixed");
+        assert_eq!(res.transfer_encoding(), "identity");
+    }
+
+    // TEST - set custom header
+    #[test]
+    fn test_set_header() {
+        let (_c, mut s) = tcp_pair();
+        let mut res = Response::new(&mut s);
+        res.set_header("X-Test", "123");
+        assert_eq!(res.get_header("X-Test"), Some(&"123".to_string()));
+    }
+
+    // TEST - invalid header names are rejected
+    #[test]
+    fn test_set_header_rejects_invalid_name() {
+        let (_c, mut s) = tcp_pair();
+        let mut res = Response::new(&mut s);
+        res.set_header("X-Valid_Name.1", "ok");
+        res.set_header("X Custom", "nope");
+        assert_eq!(res.get_header("X-Valid_Name.1"), Some(&"ok".to_string()));
+        assert_eq!(res.get_header("X Custom"), None);
+    }
+
+    // TEST - header values can't smuggle in extra header lines
+    #[test]
+    fn test_set_header_rejects_crlf_value() {
+        let mut out: Vec<u8> = Vec::new();
+        let mut res = Response::new(&mut out);
+        res.set_header("X-Name", "a\r\nInjected: 1")
+            .set_header("X-Nul", "a\0b")
+            .with_header("X-Line", "a\nb")
+            .set_header("X-Ok", "fine")
+            .send("ok");
+        let out = String::from_utf8(out).unwrap();
+        assert!(!out.contains("Injected") && !out.contains("X-Name"));
+        assert!(!out.contains("X-Nul") && !out.contains("X-Line"));
+        assert!(out.contains("X-Ok: fine\r\n"));
+    }
+
+    // TEST - Accept-Ranges can be disabled or overridden
+    #[test]
+    fn test_accept_ranges_opt_out() {
+        let tmp_file = "test_accept_ranges.html";
+        fs::write(tmp_file, "<p>hi</p>").unwrap();
+
+        let (client, mut s) = tcp_pair();
+        let mut options = ResponseOptions::new();
+        options.accept_ranges = false;
+        Response::new(&mut s)
+            .set_options(&options)
+            .html_file(tmp_file);
+        drop(s);
+        assert!(!read_all(client).contains("Accept-Ranges"));
+
+        let (client, mut s) = tcp_pair();
+        let mut res = Response::new(&mut s);
+        res.set_header("Accept-Ranges", "none").send_file(tmp_file);
+        drop(s);
+        fs::remove_file(tmp_file).unwrap();
+        assert!(read_all(client).contains("Accept-Ranges: none\r\n"));
+    }
+
+    // TEST - header order is stable across identical responses
+    #[test]
+    fn test_header_order_is_deterministic() {
+        let render = || {
+            let (client, mut s) = tcp_pair();
+            let mut res = Response::new(&mut s);
+            for i in 0..16 {
+                res.set_header(&format!("X-Header-{}", i), "v");
+            }
+            res.set_header("x-header-3", "replaced").send("ok");
+            drop(s);
+            read_all(client)
+        };
+
+        let first = render();
+        assert_eq!(first, render());
+        let pos = |name: &str| first.find(name).unwrap();
+        assert!(
+            pos("X-Header-2:") < pos("x-header-3: replaced")
+                && pos("x-header-3:") < pos("X-Header-4:")
+        );
+    }
+
+    // TEST - removed headers are not written
+    #[test]
+    fn test_remove_header() {
+        let (client, mut s) = tcp_pair();
+        let mut res = Response::new(&mut s);
+        res.set_header("X-Secret", "1").set_header("X-Keep", "2");
+        res.remove_header("x-secret").send("ok");
+        drop(s);
+
+        let out = read_all(client);
+        assert!(!out.contains("X-Secret"));
+        assert!(out.contains("X-Keep: 2\r\n"));
+    }
+
+    // TEST - sent status from HttpStatus enum
+    #[test]
+    fn test_status_with_enum() {
+        let (_c, mut s) = tcp_pair();
+        let mut res = Response::new(&mut s);
         res.status(HttpStatus::Forbidden);
         assert_eq!(res.status_code, 403);
         assert_eq!(res.status_reason, "Forbidden");
@@ -305,7 +1692,7 @@ mod tests {
         let mut res = Response::new(&mut s);
         res.send("hello");
         assert_eq!(
-            res.headers.get("Content-Type"),
+            res.get_header("Content-Type"),
             Some(&"text/plain".to_string())
         );
 
@@ -313,11 +1700,259 @@ mod tests {
         let mut res2 = Response::new(&mut s2);
         res2.json(r#"{"msg":"ok"}"#);
         assert_eq!(
-            res2.headers.get("Content-Type"),
+            res2.get_header("Content-Type"),
             Some(&"application/json".to_string())
         );
     }
 
+    // TEST - optional trailing newline on send()
+    #[test]
+    fn test_send_trailing_newline() {
+        let (c, mut s) = tcp_pair();
+        let mut res = Response::new(&mut s);
+        res.send("plain");
+        drop(s);
+        let out = read_all(c);
+        assert!(out.contains("Content-Length: 5\r\n"));
+        assert!(out.ends_with("\r\n\r\nplain"));
+
+        let mut options = ResponseOptions::new();
+        options.trailing_newline = true;
+        let (c, mut s) = tcp_pair();
+        let mut res = Response::new(&mut s);
+        res.set_options(&options);
+        res.send("plain");
+        drop(s);
+        let out = read_all(c);
+        assert!(out.contains("Content-Length: 6\r\n"));
+        assert!(out.ends_with("\r\n\r\nplain\n"));
+    }
+
+    // TEST - send a full response from parts
+    #[test]
+    fn test_send_parts() {
+        let (c, mut s) = tcp_pair();
+        let mut res = Response::new(&mut s);
+        res.send_parts(202, &[("X-One", "1"), ("X-Two", "2")], b"parts body");
+        res.send("ignored");
+        drop(s);
+
+        let out = read_all(c);
+        assert!(out.starts_with("HTTP/1.1 202 Accepted\r\n"));
+        assert!(out.contains("X-One: 1\r\n"));
+        assert!(out.contains("X-Two: 2\r\n"));
+        assert!(out.ends_with("\r\n\r\nparts body"));
+    }
+
+    // TEST - builder steps chain into a terminal body call
+    #[test]
+    fn test_with_status_and_header() {
+        let (c, mut s) = tcp_pair();
+        let mut res = Response::new(&mut s);
+        res.with_status(201)
+            .with_header("X-Id", "5")
+            .with_status(HttpStatus::Accepted)
+            .json(r#"{"id":5}"#);
+        res.with_status(500).send("ignored");
+        drop(s);
+
+        let out = read_all(c);
+        assert!(out.starts_with("HTTP/1.1 202 Accepted\r\n"));
+        assert!(out.contains("X-Id: 5\r\n"));
+        assert!(out.ends_with("\r\n\r\n{\"id\":5}"));
+    }
+
+    // TEST - empty bodies are framed with Content-Length: 0
+    #[test]
+    fn test_empty_bodies() {
+        let mut options = ResponseOptions::new();
+        options.trailing_newline = true;
+
+        let senders: [fn(&mut Response); 3] = [|r| r.send(""), |r| r.json(""), |r| r.html("")];
+        for send in senders {
+            let (client, mut s) = tcp_pair();
+            let mut res = Response::new(&mut s);
+            res.set_options(&options);
+            send(&mut res);
+            assert_eq!(res.bytes_written(), 0);
+            drop(s);
+
+            let out = read_all(client);
+            assert!(out.contains("\r\nContent-Length: 0\r\n\r\n"));
+            assert!(out.ends_with("\r\n\r\n"));
+        }
+    }
+
+    // TEST - status_enum chains like status()
+    #[test]
+    fn test_status_enum_teapot() {
+        let (client, mut s) = tcp_pair();
+        let mut res = Response::new(&mut s);
+        res.status_enum(HttpStatus::ImATeapot).send("🫖");
+        assert_eq!(res.status_code(), 418);
+        drop(s);
+        assert!(read_all(client).starts_with("HTTP/1.1 418 I'm a Teapot\r\n"));
+
+        // enums work directly wherever a status is taken
+        let (client, mut s) = tcp_pair();
+        Response::new(&mut s).send_parts(HttpStatus::Created, &[], b"made");
+        drop(s);
+        assert!(read_all(client).starts_with("HTTP/1.1 201 Created\r\n"));
+    }
+
+    // TEST - html_file only leaks the path with debug errors on
+    #[test]
+    fn test_html_file_error_detail() {
+        let mut options = ResponseOptions::new();
+
+        options.debug_errors = false;
+        let (c, mut s) = tcp_pair();
+        let mut res = Response::new(&mut s);
+        res.set_options(&options);
+        res.html_file("secret/dir/missing.html");
+        drop(s);
+        let out = read_all(c);
+        assert!(out.starts_with("HTTP/1.1 500 Internal Server Error"));
+        assert!(!out.contains("secret/dir"));
+
+        options.debug_errors = true;
+        let (c, mut s) = tcp_pair();
+        let mut res = Response::new(&mut s);
+        res.set_options(&options);
+        res.html_file("secret/dir/missing.html");
+        drop(s);
+        assert!(read_all(c).contains("No file found on secret/dir/missing.html"));
+    }
+
+    // reader that fails the test if asked for more than one chunk at a time
+    struct ChunkCheckedReader {
+        remaining: usize,
+        max_request: usize,
+    }
+
+    impl Read for ChunkCheckedReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.max_request = self.max_request.max(buf.len());
+            let n = buf.len().min(self.remaining);
+            buf[..n].fill(b'x');
+            self.remaining -= n;
+            Ok(n)
+        }
+    }
+
+    // TEST - send_reader streams in bounded chunks
+    #[test]
+    fn test_send_reader_uses_bounded_chunks() {
+        let len = 3 * 1024 * 1024;
+        let (c, mut s) = tcp_pair();
+        let reader_thread = std::thread::spawn(move || read_all(c));
+
+        let mut reader = ChunkCheckedReader {
+            remaining: len,
+            max_request: 0,
+        };
+        let mut res = Response::new(&mut s);
+        res.send_reader(&mut reader, len as u64, "application/octet-stream")
+            .unwrap();
+        assert_eq!(res.bytes_written(), len);
+        drop(s);
+
+        assert!(reader.max_request <= CHUNK_SIZE);
+        let out = reader_thread.join().unwrap();
+        assert!(out.contains(&format!("Content-Length: {}\r\n", len)));
+        assert!(out.ends_with(&"x".repeat(1024)));
+    }
+
+    // TEST - a reader failing mid-stream marks the response as broken
+    #[test]
+    fn test_send_reader_read_error() {
+        struct FailingReader {
+            calls: usize,
+        }
+        impl Read for FailingReader {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                self.calls += 1;
+                match self.calls {
+                    1 => Err(io::ErrorKind::Interrupted.into()),
+                    2 => {
+                        buf[..4].copy_from_slice(b"part");
+                        Ok(4)
+                    }
+                    _ => Err(io::Error::other("disk gone")),
+                }
+            }
+        }
+
+        let mut out: Vec<u8> = Vec::new();
+        let mut res = Response::new(&mut out);
+        let err = res
+            .send_reader(FailingReader { calls: 0 }, 10, "text/plain")
+            .unwrap_err();
+        assert_eq!(err.to_string(), "disk gone");
+        assert!(res.write_failed());
+        assert!(String::from_utf8(out).unwrap().ends_with("\r\n\r\npart"));
+    }
+
+    // TEST - send_file streams a multi-megabyte file intact
+    #[test]
+    fn test_send_file_streams_large_file() {
+        let tmp_file = "test_send_file.bin";
+        let content: Vec<u8> = (0..4 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+        fs::write(tmp_file, &content).unwrap();
+
+        let (mut c, mut s) = tcp_pair();
+        let reader_thread = std::thread::spawn(move || {
+            let mut out = Vec::new();
+            c.read_to_end(&mut out).unwrap();
+            out
+        });
+
+        let mut res = Response::new(&mut s);
+        res.send_file(tmp_file);
+        drop(s);
+        fs::remove_file(tmp_file).unwrap();
+
+        let out = reader_thread.join().unwrap();
+        let split = out.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        let head = String::from_utf8_lossy(&out[..split]);
+        assert!(head.contains("Content-Type: application/octet-stream"));
+        assert!(head.contains(&format!("Content-Length: {}", content.len())));
+        assert!(out[split + 4..] == content[..]);
+
+        assert!(head.contains("Accept-Ranges: bytes"));
+
+        // missing file -> 404
+        let (_c2, mut s2) = tcp_pair();
+        let mut res2 = Response::new(&mut s2);
+        res2.send_file("missing_file.bin");
+        assert_eq!(res2.status_code, 404);
+    }
+
+    // TEST - body size is tracked after sending
+    #[test]
+    fn test_bytes_written() {
+        let (_c, mut s) = tcp_pair();
+        let mut res = Response::new(&mut s);
+        assert_eq!(res.bytes_written(), 0);
+        res.json(r#"{"size":12}"#);
+        assert_eq!(res.bytes_written(), 11);
+    }
+
+    // TEST - json_checked only sends well-formed JSON
+    #[test]
+    fn test_json_checked() {
+        let (_c, mut s) = tcp_pair();
+        let mut res = Response::new(&mut s);
+        assert!(res.json_checked(r#"{"ok":true}"#).is_ok());
+        assert!(res.sent);
+
+        let (_c2, mut s2) = tcp_pair();
+        let mut res2 = Response::new(&mut s2);
+        let err = res2.json_checked(r#"{"ok":tru}"#).unwrap_err();
+        assert!(err.contains("invalid JSON"));
+        assert!(!res2.sent);
+    }
+
     // TEST - html response sets proper content type
     #[test]
     fn test_html_sets_content_type() {
@@ -326,11 +1961,82 @@ mod tests {
         res.html("<h1>Test</h1>");
         assert!(res.sent);
         assert_eq!(
-            res.headers.get("Content-Type"),
+            res.get_header("Content-Type"),
             Some(&"text/html; charset=utf-8".to_string())
         );
     }
 
+    // TEST - error JSON has a fixed shape, an escaped message, and the status
+    #[test]
+    fn test_error_json() {
+        let mut out: Vec<u8> = Vec::new();
+        let mut res = Response::new(&mut out);
+        res.error(422, "field \"email\" is\tinvalid");
+        // a late error() leaves the sent status and body alone
+        res.error(500, "too late");
+        assert_eq!(res.status_code(), 422);
+        assert_eq!(
+            res.get_header("Content-Type"),
+            Some(&"application/json".to_string())
+        );
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("HTTP/1.1 422 Unprocessable Entity\r\n"));
+        let body = out.split("\r\n\r\n").nth(1).unwrap();
+        assert_eq!(
+            body,
+            r#"{"error":{"code":422,"message":"field \"email\" is\tinvalid"}}"#
+        );
+        assert_eq!(
+            Json::parse(body).unwrap(),
+            Json::Object(vec![(
+                "error".into(),
+                Json::Object(vec![
+                    ("code".into(), Json::Number(422.0)),
+                    (
+                        "message".into(),
+                        Json::String("field \"email\" is\tinvalid".into())
+                    ),
+                ]),
+            )])
+        );
+    }
+
+    // TEST - 304 keeps the validators and sends no body
+    #[test]
+    fn test_not_modified() {
+        let mut out: Vec<u8> = Vec::new();
+        let mut res = Response::new(&mut out);
+        res.set_header("ETag", "\"v1\"")
+            .set_header("Cache-Control", "max-age=60")
+            .set_header("Content-Type", "image/png")
+            .not_modified();
+        assert_eq!(res.status_code(), 304);
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("HTTP/1.1 304 Not Modified\r\n"));
+        assert!(out.contains("ETag: \"v1\"\r\n"));
+        assert!(out.contains("Cache-Control: max-age=60\r\n"));
+        assert!(!out.contains("Content-Type") && !out.contains("Content-Length"));
+        assert!(out.ends_with("\r\n\r\n"));
+    }
+
+    // TEST - binary bodies go out untouched, once
+    #[test]
+    fn test_send_bytes() {
+        let mut out: Vec<u8> = Vec::new();
+        let mut res = Response::new(&mut out);
+        res.send_bytes(&[0x00, 0xff, 0xfe], "application/octet-stream");
+        res.send_bytes(b"ignored", "text/plain");
+        assert_eq!(
+            res.get_header("Content-Type"),
+            Some(&"application/octet-stream".to_string())
+        );
+
+        assert!(String::from_utf8_lossy(&out).contains("Content-Length: 3\r\n"));
+        assert!(out.ends_with(b"\r\n\r\n\x00\xff\xfe"));
+    }
+
     // TEST - html_file loads file contents
     #[test]
     fn test_html_file_success_and_failure() {
@@ -343,7 +2049,7 @@ mod tests {
         res.html_file(tmp_file);
         assert!(res.sent);
         assert_eq!(
-            res.headers.get("Content-Type"),
+            res.get_header("Content-Type"),
             Some(&"text/html; charset=utf-8".to_string())
         );
 
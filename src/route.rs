@@ -1,52 +1,242 @@
+use crate::parser::url_decode;
 use crate::request::Request;
-use crate::server::Handler;
+use crate::server::{Handler, TryHandler};
+
+/// The param a trailing `*` segment captures into.
+const WILDCARD: &str = "wildcard";
+
+/// Params captured from a path, plus a repeating param's segments
+type Captures<'a> = (Vec<(&'a str, String)>, Option<(&'a str, Vec<String>)>);
+
+/// The handler attached to a [`Route`].
+#[derive(Clone, Copy)]
+pub enum RouteHandler {
+    /// A plain handler
+    Plain(Handler),
+    /// A handler returning `Result<(), RxError>`
+    Fallible(TryHandler),
+}
+
+/// The request methods a [`Route`] accepts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Method {
+    /// Every method, for catch-all routes
+    Any,
+    /// A single method, like `GET`
+    Exact(String),
+}
+
+impl Method {
+    /// Checks whether a request with `method` is accepted.
+    pub fn matches(&self, method: &str) -> bool {
+        match self {
+            Method::Any => true,
+            Method::Exact(exact) => exact == method,
+        }
+    }
+
+    /// Returns the method name, or `*` for [`Method::Any`].
+    pub fn as_str(&self) -> &str {
+        match self {
+            Method::Any => "*",
+            Method::Exact(exact) => exact,
+        }
+    }
+}
 
 /// Represents a single route definition (method + path + handler).
 pub struct Route {
-    pub method: String,
+    pub method: Method,
     pub path: String,
-    pub handler: Handler,
+    pub handler: RouteHandler,
 }
 
 impl Route {
     /// Creates a new [`Route`].
     pub fn new(method: &str, path: &str, handler: Handler) -> Route {
         Route {
-            method: method.to_string(),
+            method: Method::Exact(method.to_string()),
+            path: path.to_string(),
+            handler: RouteHandler::Plain(handler),
+        }
+    }
+
+    /// Creates a new [`Route`] that accepts every method.
+    pub fn any(path: &str, handler: Handler) -> Route {
+        Route {
+            method: Method::Any,
             path: path.to_string(),
-            handler,
+            handler: RouteHandler::Plain(handler),
+        }
+    }
+
+    /// Creates a new [`Route`] with a fallible handler.
+    pub fn fallible(method: &str, path: &str, handler: TryHandler) -> Route {
+        Route {
+            method: Method::Exact(method.to_string()),
+            path: path.to_string(),
+            handler: RouteHandler::Fallible(handler),
         }
     }
 
     /// Checks if this route matches a given request.
     ///
-    /// Supports path parameters like `/users/:id`.
+    /// Supports path parameters like `/users/:id`, including parameters followed
+    /// by a literal suffix in the same segment, like `/images/:name.png`.
+    /// A trailing `:name+` captures one or more remaining segments, joined with
+    /// `/` (see [`Request::param_all`]).
+    /// A trailing `*` captures the rest of the path, empty or not, into the
+    /// `wildcard` param: `/files/*` matches `/files/a/b/c.txt` with
+    /// `wildcard = "a/b/c.txt"`.
+    ///
+    /// When several routes match a request, the one registered first wins, so
+    /// register specific routes like `/files/index.html` before `/files/*`.
+    ///
+    /// Captured values are percent-decoded (see [`url_decode`]).
+    ///
+    /// An empty segment never fills a param (`/users/` doesn't match `/users/:id`)
+    /// unless it is marked optional with `?`: `/users/:id?` matches `/users/7`,
+    /// `/users/`, and `/users`, leaving `id` unset for the last two.
     pub fn matches(&self, req: &mut Request) -> bool {
-        if self.method != req.method.to_uppercase() {
+        let method = req.method.to_uppercase();
+        self.matches_as(&method, req)
+    }
+
+    /// Like [`Route::matches`], but as if the request used `method`.
+    pub(crate) fn matches_as(&self, method: &str, req: &mut Request) -> bool {
+        if !self.method.matches(method) {
             return false;
         }
 
-        let route_parts: Vec<&str> = self.path.split('/').collect();
-        let req_parts: Vec<&str> = req.path.split('/').collect();
+        let (params, segments) = match self.capture(&req.path) {
+            Some(captured) => captured,
+            None => return false,
+        };
 
-        if route_parts.len() != req_parts.len() {
-            return false;
+        for (key, value) in params {
+            req.set_param_segments(key, None);
+            req.params.insert(key.to_string(), value);
+        }
+        if let Some((key, segments)) = segments {
+            req.set_param_segments(key, Some(segments));
+        }
+        req.set_param_names(self.param_names());
+
+        true
+    }
+
+    /// Lists the parameter names declared by the path pattern, in order
+    /// (`/users/:id/posts/:pid` -> `["id", "pid"]`).
+    pub fn param_names(&self) -> Vec<String> {
+        self.path
+            .split('/')
+            .filter_map(|part| match part {
+                "*" => Some(WILDCARD),
+                _ => part
+                    .strip_prefix(':')
+                    .map(|param| Self::split_param(param).0),
+            })
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Checks if this route's path matches `path`, whatever the method.
+    pub fn matches_path(&self, path: &str) -> bool {
+        self.capture(path).is_some()
+    }
+
+    /// Builds a concrete path that matches this route, filling every param with `1`
+    /// (`/users/:id/:file.png` -> `/users/1/1.png`).
+    pub(crate) fn sample_path(&self) -> String {
+        self.path
+            .split('/')
+            .map(|part| match part.strip_prefix(':') {
+                Some(param) => {
+                    let suffix = Self::split_param(param.trim_end_matches(['+', '?'])).1;
+                    format!("1{}", suffix)
+                }
+                None if part == "*" => "1".to_string(),
+                None => part.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /*---- Private Functions ----*/
+    /// Matches `path` against the route pattern, returning the captured params
+    /// and, for a repeating param, its segments decoded one by one.
+    fn capture(&self, path: &str) -> Option<Captures<'_>> {
+        let mut route_parts: Vec<&str> = self.path.split('/').collect();
+        let req_parts: Vec<&str> = path.split('/').collect();
+
+        // `/tags/:tag+` and `/files/*` -> the last part swallows every remaining segment
+        let wildcard = route_parts.last() == Some(&"*");
+        let repeat = match wildcard {
+            true => Some(WILDCARD),
+            false => route_parts
+                .last()
+                .and_then(|r| r.strip_prefix(':'))
+                .and_then(|r| r.strip_suffix('+')),
+        };
+        if repeat.is_some() {
+            route_parts.pop();
+            if req_parts.len() <= route_parts.len() {
+                return None;
+            }
+        } else if route_parts.len() != req_parts.len() {
+            // `/users/:id?` also matches `/users`
+            let optional_tail = route_parts
+                .last()
+                .is_some_and(|r| r.starts_with(':') && r.ends_with('?'));
+            if !(optional_tail && req_parts.len() + 1 == route_parts.len()) {
+                return None;
+            }
         }
 
+        let mut params = Vec::new();
         for (r, p) in route_parts.iter().zip(req_parts.iter()) {
-            if r.starts_with(':') {
-                // store param
-                let key = r.trim_start_matches(':').to_string();
-                if !p.is_empty() {
-                    req.params.insert(key, p.to_string());
+            if let Some(param) = r.strip_prefix(':') {
+                let optional = param.strip_suffix('?');
+                let (key, suffix) = Self::split_param(optional.unwrap_or(param));
+                if suffix.is_empty() {
+                    // store param; an empty segment only fits an optional one
+                    if !p.is_empty() {
+                        params.push((key, url_decode(p)));
+                    } else if optional.is_none() {
+                        return None;
+                    }
+                } else {
+                    // `:name.png` -> capture everything before the literal suffix
+                    match p.strip_suffix(suffix) {
+                        Some(value) if !value.is_empty() => params.push((key, url_decode(value))),
+                        _ => return None,
+                    }
                 }
-                println!("[params]: {:?}", req.params);
             } else if r != p {
-                return false;
+                return None;
             }
         }
 
-        true
+        let mut segments = None;
+        if let Some(key) = repeat {
+            let rest = &req_parts[route_parts.len()..];
+            if !wildcard && rest.iter().any(|p| p.is_empty()) {
+                return None;
+            }
+            let rest: Vec<String> = rest.iter().map(|p| url_decode(p)).collect();
+            params.push((key, rest.join("/")));
+            segments = Some((key, rest));
+        }
+
+        Some((params, segments))
+    }
+
+    /// Splits `name.png` into the param name (`name`) and its literal suffix (`.png`).
+    fn split_param(param: &str) -> (&str, &str) {
+        let end = param
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(param.len());
+        param.split_at(end)
     }
 }
 
@@ -74,12 +264,132 @@ mod tests {
         assert_eq!(req.param("id"), Some(&"123".to_string()));
     }
 
+    #[test]
+    fn test_route_match_param_with_literal_suffix() {
+        let route = Route::new("GET", "/images/:name.png", dummy_handler);
+
+        let mut req = Request::new("GET /images/photo.png HTTP/1.1", HashMap::new(), "".into());
+        assert!(route.matches(&mut req));
+        assert_eq!(req.param("name"), Some(&"photo".to_string()));
+
+        let mut req = Request::new("GET /images/photo.jpg HTTP/1.1", HashMap::new(), "".into());
+        assert!(!route.matches(&mut req));
+        assert_eq!(req.param("name"), None);
+
+        let mut req = Request::new("GET /images/.png HTTP/1.1", HashMap::new(), "".into());
+        assert!(!route.matches(&mut req));
+    }
+
+    #[test]
+    fn test_route_match_repeating_param() {
+        let route = Route::new("GET", "/tags/:tag+", dummy_handler);
+
+        let mut req = Request::new("GET /tags/rust/web HTTP/1.1", HashMap::new(), "".into());
+        assert!(route.matches(&mut req));
+        assert_eq!(req.param_all("tag"), vec!["rust", "web"]);
+
+        let mut req = Request::new("GET /tags/rust HTTP/1.1", HashMap::new(), "".into());
+        assert!(route.matches(&mut req));
+        assert_eq!(req.param_all("tag"), vec!["rust"]);
+
+        // one or more: a bare `/tags` or empty segments don't match
+        let mut req = Request::new("GET /tags HTTP/1.1", HashMap::new(), "".into());
+        assert!(!route.matches(&mut req));
+        let mut req = Request::new("GET /tags/rust//web HTTP/1.1", HashMap::new(), "".into());
+        assert!(!route.matches(&mut req));
+    }
+
+    #[test]
+    fn test_route_param_names() {
+        let route = Route::new("GET", "/users/:id/posts/:pid", dummy_handler);
+        let mut req = Request::new("GET /users/1/posts/2 HTTP/1.1", HashMap::new(), "".into());
+        assert!(route.matches(&mut req));
+        assert_eq!(req.param_names(), vec!["id", "pid"]);
+
+        let route = Route::new("GET", "/files/:name.png/:rest+", dummy_handler);
+        assert_eq!(route.param_names(), vec!["name", "rest"]);
+    }
+
+    // TEST - one `Any` route serves every method
+    #[test]
+    fn test_route_any_method() {
+        let route = Route::any("/ping", dummy_handler);
+        assert_eq!(route.method.as_str(), "*");
+        for method in ["GET", "POST", "DELETE"] {
+            let mut req = Request::new(
+                &format!("{} /ping HTTP/1.1", method),
+                HashMap::new(),
+                "".into(),
+            );
+            assert!(route.matches(&mut req), "{}", method);
+        }
+        let mut req = Request::new("GET /pong HTTP/1.1", HashMap::new(), "".into());
+        assert!(!route.matches(&mut req));
+
+        let route = Route::new("GET", "/ping", dummy_handler);
+        let mut req = Request::new("POST /ping HTTP/1.1", HashMap::new(), "".into());
+        assert!(!route.matches(&mut req));
+    }
+
     #[test]
     fn test_route_with_missing_param() {
         let route = Route::new("GET", "/users/:id", dummy_handler);
         let req = Request::new("GET /users/ HTTP/1.1", HashMap::new(), "".into());
         let mut req = req;
-        assert!(route.matches(&mut req));
+        assert!(!route.matches(&mut req));
         assert_eq!(req.param("id"), None);
     }
+
+    // TEST - captured params are percent-decoded, `+` kept
+    #[test]
+    fn test_route_param_decoding() {
+        let route = Route::new("GET", "/files/:name/:rest+", dummy_handler);
+        let mut req = Request::new(
+            "GET /files/my%20file+v2/caf%C3%A9/a%2Fb HTTP/1.1",
+            HashMap::new(),
+            "".into(),
+        );
+        assert!(route.matches(&mut req));
+        assert_eq!(req.param("name"), Some(&"my file+v2".to_string()));
+        assert_eq!(req.param_all("rest"), vec!["café", "a/b"]);
+    }
+
+    // TEST - a trailing `*` captures the rest of the path
+    #[test]
+    fn test_route_wildcard() {
+        let route = Route::new("GET", "/files/*", dummy_handler);
+        assert_eq!(route.param_names(), vec!["wildcard"]);
+        assert_eq!(route.sample_path(), "/files/1");
+
+        let mut req = Request::new("GET /files/a/b/c.txt HTTP/1.1", HashMap::new(), "".into());
+        assert!(route.matches(&mut req));
+        assert_eq!(req.param("wildcard"), Some(&"a/b/c.txt".to_string()));
+
+        let mut req = Request::new("GET /files/ HTTP/1.1", HashMap::new(), "".into());
+        assert!(route.matches(&mut req));
+        assert_eq!(req.param("wildcard"), Some(&"".to_string()));
+
+        assert!(!route.matches_path("/files"));
+        assert!(!route.matches_path("/other/a.txt"));
+    }
+
+    // TEST - optional params accept an empty or absent segment
+    #[test]
+    fn test_route_optional_param() {
+        let route = Route::new("GET", "/users/:id?", dummy_handler);
+        assert_eq!(route.param_names(), vec!["id"]);
+        assert_eq!(route.sample_path(), "/users/1");
+
+        let mut req = Request::new("GET /users/7 HTTP/1.1", HashMap::new(), "".into());
+        assert!(route.matches(&mut req));
+        assert_eq!(req.param("id"), Some(&"7".to_string()));
+
+        for path in ["/users/", "/users"] {
+            let mut req =
+                Request::new(&format!("GET {} HTTP/1.1", path), HashMap::new(), "".into());
+            assert!(route.matches(&mut req));
+            assert_eq!(req.param("id"), None);
+        }
+        assert!(!route.matches_path("/users/7/posts"));
+    }
 }
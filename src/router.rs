@@ -1,18 +1,68 @@
+//! # Router Module
+//!
+//! The [`Router`] behind every [`Server`](crate::Server): it stores routes,
+//! middleware, and static assets, and dispatches a [`Request`] to the first
+//! matching handler. It can also be used on its own, e.g. to unit-test routing
+//! against an in-memory [`Response`], or registered for a virtual host with
+//! [`Server::host_route`](crate::Server::host_route).
+//!
+//! ## Example
+//! ```
+//! use std::collections::HashMap;
+//! use rxpress::{Request, Response, Router};
+//!
+//! let mut router = Router::new();
+//! router.add_route("GET", "/users/:id", |req, res| {
+//!     res.send(&format!("User {}", req.param_or("id", "?")));
+//! });
+//!
+//! let mut req = Request::new("GET /users/7 HTTP/1.1", HashMap::new(), "".into());
+//! let mut out: Vec<u8> = Vec::new();
+//! router.handle(&mut req, &mut Response::new(&mut out));
+//!
+//! assert!(String::from_utf8(out).unwrap().ends_with("\r\n\r\nUser 7"));
+//! ```
+
 use crate::HttpStatus;
+use crate::mime;
+use crate::parser::url_decode;
 use crate::request::Request;
 use crate::response::Response;
-use crate::route::Route;
-use crate::server::Handler;
+use crate::route::{Route, RouteHandler};
+use crate::server::{Handler, Middleware, TryHandler};
+
+/// A compile-time asset map of `(file path, contents)` pairs, e.g. built with
+/// `include_bytes!`.
+pub type EmbeddedAssets = &'static [(&'static str, &'static [u8])];
 
 /// Router manages all registered routes and dispatches requests.
+///
+/// Routes are tried in registration order; see [`Router::handle`] for the
+/// full dispatch order and the fallbacks (`405`, `404`) when nothing matches.
 pub struct Router {
     routes: Vec<Route>,
+    assets: Vec<(String, EmbeddedAssets)>,
+    /// Directories served from disk, as `(url prefix, filesystem root)`
+    dirs: Vec<(String, String)>,
+    /// Middleware with the path prefix it is scoped to (`""` for every path)
+    middleware: Vec<(String, Middleware)>,
+    /// Sends the response when no route matches
+    not_found: Option<Handler>,
+    /// Redirects `/users/` to `/users` (and back) when only the other exists
+    redirect_trailing_slash: bool,
 }
 
 impl Router {
     /// Creates a new, empty [`Router`].
     pub fn new() -> Router {
-        Router { routes: Vec::new() }
+        Router {
+            routes: Vec::new(),
+            assets: Vec::new(),
+            dirs: Vec::new(),
+            middleware: Vec::new(),
+            not_found: None,
+            redirect_trailing_slash: false,
+        }
     }
 
     /// Adds a new route with method, path, and handler.
@@ -20,15 +70,688 @@ impl Router {
         self.routes.push(Route::new(method, path, handler));
     }
 
+    /// Adds a new route that accepts every method.
+    pub fn add_any_route(&mut self, path: &str, handler: Handler) {
+        self.routes.push(Route::any(path, handler));
+    }
+
+    /// Adds a new route whose handler may fail with an [`RxError`](crate::RxError).
+    pub fn add_try_route(&mut self, method: &str, path: &str, handler: TryHandler) {
+        self.routes.push(Route::fallible(method, path, handler));
+    }
+
+    /// Serves `GET`/`HEAD` requests under `prefix` from an embedded asset map.
+    pub fn add_embedded(&mut self, prefix: &str, assets: EmbeddedAssets) {
+        self.assets
+            .push((prefix.trim_end_matches('/').to_string(), assets));
+    }
+
+    /// Serves `GET`/`HEAD` requests under `prefix` from files below `root` on disk.
+    pub fn add_static_dir(&mut self, prefix: &str, root: &str) {
+        self.dirs
+            .push((prefix.trim_end_matches('/').to_string(), root.to_string()));
+    }
+
+    /// Adds middleware run, in order, before any route is matched.
+    pub fn add_middleware(&mut self, middleware: Middleware) {
+        self.middleware.push((String::new(), middleware));
+    }
+
+    /// Adds middleware that only runs for paths under `prefix`, matched on whole
+    /// segments (`/api` covers `/api` and `/api/users`, not `/apixyz`). A `:param`
+    /// segment in the prefix matches any one segment.
+    pub fn add_path_middleware(&mut self, prefix: &str, middleware: Middleware) {
+        self.middleware
+            .push((prefix.trim_end_matches('/').to_string(), middleware));
+    }
+
+    /// Moves everything registered on `router` under `prefix`: its routes and
+    /// middleware, then its embedded assets and static directories (the last two
+    /// need a literal prefix). `:param` segments in both the prefix and the
+    /// child paths are kept, so `/orgs/:org` + `/users/:id` becomes
+    /// `/orgs/:org/users/:id`.
+    ///
+    /// The mounted routes come after those already registered here. The child's
+    /// not-found handler and trailing-slash setting are not carried over.
+    ///
+    /// # Example
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rxpress::{Request, Response, Router};
+    ///
+    /// let mut api = Router::new();
+    /// api.add_route("GET", "/users/:id", |req, res| {
+    ///     res.send(&format!("{}/{}", req.param_or("v", "?"), req.param_or("id", "?")));
+    /// });
+    ///
+    /// let mut router = Router::new();
+    /// router.mount("/api/:v", api);
+    ///
+    /// let mut req = Request::new("GET /api/v1/users/7 HTTP/1.1", HashMap::new(), "".into());
+    /// let mut out: Vec<u8> = Vec::new();
+    /// router.handle(&mut req, &mut Response::new(&mut out));
+    /// assert!(String::from_utf8(out).unwrap().ends_with("\r\n\r\nv1/7"));
+    /// ```
+    pub fn mount(&mut self, prefix: &str, router: Router) {
+        let prefix = prefix.trim_end_matches('/');
+        let join = |path: &str| {
+            let path = path.trim_start_matches('/');
+            match (prefix, path) {
+                ("", "") => "/".to_string(),
+                (_, "") => prefix.to_string(),
+                _ => format!("{}/{}", prefix, path),
+            }
+        };
+
+        for mut route in router.routes {
+            route.path = join(&route.path);
+            self.routes.push(route);
+        }
+        for (scope, middleware) in router.middleware {
+            self.middleware
+                .push((format!("{}{}", prefix, scope), middleware));
+        }
+        for (scope, assets) in router.assets {
+            self.assets.push((format!("{}{}", prefix, scope), assets));
+        }
+        for (scope, root) in router.dirs {
+            self.dirs.push((format!("{}{}", prefix, scope), root));
+        }
+    }
+
+    /// Sets the handler used when no route matches, in place of the default
+    /// `404 Not Found` text. The status is already `404` when it runs.
+    pub fn set_not_found(&mut self, handler: Handler) {
+        self.not_found = Some(handler);
+    }
+
+    /// Turns on `301` redirects between a path with and without its trailing
+    /// slash, when no route matches the request but one matches the other form.
+    pub fn redirect_trailing_slash(&mut self, enabled: bool) {
+        self.redirect_trailing_slash = enabled;
+    }
+
+    /// Lists the methods registered for routes matching `path`, in registration
+    /// order and without duplicates. Routes accepting any method list `*`.
+    pub fn allowed_methods(&self, path: &str) -> Vec<&str> {
+        let mut methods: Vec<&str> = Vec::new();
+        for route in &self.routes {
+            let method = route.method.as_str();
+            if route.matches_path(path) && !methods.contains(&method) {
+                methods.push(method);
+            }
+        }
+        methods
+    }
+
     /// Dispatches a request to the first matching route handler.
+    ///
+    /// When routes match the path but not the method, the reply is
+    /// `405 Method Not Allowed` with an `Allow` header, or `204 No Content` with
+    /// the same header for an `OPTIONS` request no route handles; only paths
+    /// that match nothing get a `404`.
+    ///
+    /// Middleware runs first and may stop dispatching by returning `false`. Then
+    /// embedded assets are checked; unknown assets fall through to the
+    /// routes. Paths under a static directory are always answered from disk,
+    /// with a `404` for missing files. A handler that returns without sending
+    /// anything gets an empty `200 OK` (or whatever status it set), so the
+    /// client never waits forever.
     pub fn handle(&self, req: &mut Request, res: &mut Response) {
-        for route in &self.routes {
-            if route.matches(req) {
-                (route.handler)(req, res);
-                return;
+        let proceed = self
+            .middleware
+            .iter()
+            .all(|(prefix, middleware)| !Self::in_scope(prefix, &req.path) || middleware(req, res));
+        if !proceed {
+            if !res.is_sent() {
+                res.end();
+            }
+            return;
+        }
+
+        if self.serve_embedded(req, res) || self.serve_static_dir(req, res) {
+            return;
+        }
+
+        let mut route = self.routes.iter().find(|route| route.matches(req));
+        // HEAD falls back to the GET route; the server leaves the body out
+        if route.is_none() && req.method.eq_ignore_ascii_case("HEAD") {
+            route = self
+                .routes
+                .iter()
+                .find(|route| route.matches_as("GET", req));
+        }
+
+        if let Some(route) = route {
+            match route.handler {
+                RouteHandler::Plain(handler) => handler(req, res),
+                RouteHandler::Fallible(handler) => {
+                    if let Err(err) = handler(req, res) {
+                        res.send_error(&err);
+                    }
+                }
+            }
+            if !res.is_sent() {
+                res.end();
+            }
+            return;
+        }
+
+        if self.redirect_trailing_slash
+            && let Some(location) = self.canonical_location(req)
+        {
+            res.redirect_with(301, &location);
+            return;
+        }
+
+        // the path exists, just not for this method
+        let mut allowed = self.allowed_methods(&req.path);
+        if !allowed.is_empty() {
+            if allowed.contains(&"GET") && !allowed.contains(&"HEAD") {
+                allowed.push("HEAD"); // answered by the GET route
+            }
+            if !allowed.contains(&"OPTIONS") {
+                allowed.push("OPTIONS"); // answered below
+            }
+            res.set_header("Allow", &allowed.join(", "));
+            if req.method.eq_ignore_ascii_case("OPTIONS") {
+                res.status(204).end();
+            } else {
+                res.status(HttpStatus::MethodNotAllowed)
+                    .send("405 Method Not Allowed");
+            }
+            return;
+        }
+
+        res.status(HttpStatus::NotFound);
+        if let Some(not_found) = self.not_found {
+            not_found(req, res);
+            if !res.is_sent() {
+                res.end();
+            }
+            return;
+        }
+
+        // default 404, negotiated between plain text and JSON
+        match req.accepts(&["text/plain", "application/json"]) {
+            Some("application/json") => res.json(r#"{"error":"404 Not Found"}"#),
+            _ => res.send("404 Not Found"),
+        }
+    }
+
+    /// Lists the registered routes, in registration order.
+    pub(crate) fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+
+    /*---- Private Functions ----*/
+    /// Checks whether `path` lies under the middleware scope `prefix`, segment by
+    /// segment; `:param` segments match any one segment.
+    fn in_scope(prefix: &str, path: &str) -> bool {
+        let mut segments = path.split('/');
+        prefix.split('/').all(|part| match segments.next() {
+            Some(segment) if part.starts_with(':') => !segment.is_empty(),
+            Some(segment) => segment == part,
+            None => false,
+        })
+    }
+
+    /// Finds the other form of the request path (trailing slash added or
+    /// removed) if a route serves it, keeping the query string.
+    fn canonical_location(&self, req: &Request) -> Option<String> {
+        let path = req.path.as_str();
+        let other = match path.strip_suffix('/') {
+            Some("") => return None, // `/` has no other form
+            Some(trimmed) => trimmed.to_string(),
+            None => format!("{}/", path),
+        };
+
+        let method = req.method.to_uppercase();
+        let served = self.routes.iter().any(|route| {
+            route.matches_path(&other)
+                && (route.method.matches(&method)
+                    || method == "HEAD" && route.method.matches("GET"))
+        });
+        if !served {
+            return None;
+        }
+
+        Some(match req.raw_query() {
+            Some(query) => format!("{}?{}", other, query),
+            None => other,
+        })
+    }
+
+    /// Sends the file for `req` from a static directory, returning `false` if
+    /// the path is outside every registered prefix.
+    ///
+    /// Segments are percent-decoded before checking, and any `..` segment (or
+    /// one hiding a separator or NUL byte) is refused with `403 Forbidden`, so a
+    /// request can never reach outside the directory root.
+    fn serve_static_dir(&self, req: &Request, res: &mut Response) -> bool {
+        let method = req.method.to_uppercase();
+        if method != "GET" && method != "HEAD" {
+            return false;
+        }
+
+        for (prefix, root) in &self.dirs {
+            let rest = match req.path.strip_prefix(prefix.as_str()) {
+                Some(rest) if rest.starts_with('/') => rest,
+                _ => continue,
+            };
+
+            let mut path = std::path::PathBuf::from(root);
+            for segment in rest.split('/').map(url_decode) {
+                if segment == ".." || segment.contains(['/', '\\', '\0']) {
+                    res.status(HttpStatus::Forbidden).send("403 Forbidden");
+                    return true;
+                }
+                if !segment.is_empty() && segment != "." {
+                    path.push(segment);
+                }
+            }
+
+            res.file(&path.to_string_lossy());
+            return true;
+        }
+
+        false
+    }
+
+    /// Sends the embedded asset for `req`, returning `false` if there is none.
+    fn serve_embedded(&self, req: &Request, res: &mut Response) -> bool {
+        let method = req.method.to_uppercase();
+        if method != "GET" && method != "HEAD" {
+            return false;
+        }
+
+        for (prefix, assets) in &self.assets {
+            let key = match req.path.strip_prefix(prefix.as_str()) {
+                Some(rest) if rest.starts_with('/') => rest.trim_start_matches('/'),
+                _ => continue,
+            };
+            if let Some((path, data)) = assets
+                .iter()
+                .find(|(path, _)| path.trim_start_matches('/') == key)
+            {
+                res.send_parts(200, &[("Content-Type", mime::from_path(path))], data);
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+impl Default for Router {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::Read;
+    use std::net::{TcpListener, TcpStream};
+
+    // helper to dispatch a request through the router and read the raw response
+    fn dispatch(router: &Router, mut req: Request) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let mut stream = listener.accept().unwrap().0;
+
+        router.handle(&mut req, &mut Response::new(&mut stream));
+        drop(stream);
+
+        let mut out = String::new();
+        client.read_to_string(&mut out).unwrap();
+        out
+    }
+
+    fn req_with_accept(accept: &str) -> Request {
+        let mut headers = HashMap::new();
+        headers.insert("accept".to_string(), accept.to_string());
+        Request::new("GET /missing HTTP/1.1", headers, "".into())
+    }
+
+    // TEST - middleware runs before matching and can stop dispatching
+    #[test]
+    fn test_middleware() {
+        let mut router = Router::new();
+        router.add_middleware(|_req, res| {
+            res.set_header("X-Seen", "1");
+            true
+        });
+        router.add_middleware(|req, res| {
+            if req.header("authorization").is_none() {
+                res.status(401).send("Unauthorized");
+                return false;
             }
+            true
+        });
+        router.add_route("GET", "/secret", |_req, res| res.send("secret"));
+
+        // stopped before the route, and before the 404 fallback
+        let req = Request::new("GET /secret HTTP/1.1", HashMap::new(), "".into());
+        let out = dispatch(&router, req);
+        assert!(out.starts_with("HTTP/1.1 401 Unauthorized"));
+        assert!(out.contains("X-Seen: 1\r\n") && out.ends_with("\r\n\r\nUnauthorized"));
+
+        let mut headers = HashMap::new();
+        headers.insert("authorization".to_string(), "token".to_string());
+        let req = Request::new("GET /secret HTTP/1.1", headers.clone(), "".into());
+        assert!(dispatch(&router, req).ends_with("\r\n\r\nsecret"));
+
+        // runs even when no route matches
+        let req = Request::new("GET /missing HTTP/1.1", headers, "".into());
+        let out = dispatch(&router, req);
+        assert!(out.starts_with("HTTP/1.1 404 Not Found") && out.contains("X-Seen: 1\r\n"));
+    }
+
+    // TEST - middleware buffers a streamed body to sign it, the handler re-reads it
+    #[test]
+    fn test_buffer_streamed_body() {
+        let mut router = Router::new();
+        router.add_middleware(|req, res| match req.buffer_body() {
+            Ok(body) => {
+                res.set_header("X-Body-Length", &body.len().to_string());
+                true
+            }
+            Err(err) => {
+                res.status(err.status()).send(err.message());
+                false
+            }
+        });
+        router.add_route("POST", "/orders", |req, res| match req.json() {
+            Ok(order) => res.send(&order.to_string()),
+            Err(err) => res.status(400).send(&err),
+        });
+
+        let headers = HashMap::from([("content-type".into(), "application/json".into())]);
+        let mut req = Request::new("POST /orders HTTP/1.1", headers, "".into());
+        req.set_body_stream(r#"{"id": 42}"#.as_bytes());
+        let out = dispatch(&router, req);
+        assert!(out.contains("X-Body-Length: 10\r\n"));
+        assert!(out.ends_with("\r\n\r\n{\"id\":42}"));
+
+        // over the limit: 413, and the body stays unavailable
+        let mut req = Request::new("POST /orders HTTP/1.1", HashMap::new(), "".into());
+        req.set_body_stream(r#"{"id": 42}"#.as_bytes());
+        req.set_body_limit(8);
+        assert_eq!(req.buffer_body().unwrap_err().status(), 413);
+        assert!(req.buffer_body().is_err());
+        assert!(dispatch(&router, req).starts_with("HTTP/1.1 413 Payload Too Large"));
+    }
+
+    // TEST - path middleware only fires under its prefix, on whole segments
+    #[test]
+    fn test_path_middleware() {
+        let mut router = Router::new();
+        router.add_path_middleware("/api/", |_req, res| {
+            res.set_header("X-Api", "1");
+            true
+        });
+        router.add_route("GET", "/api", |_req, res| res.send("api"));
+        router.add_route("GET", "/api/users", |_req, res| res.send("users"));
+        router.add_route("GET", "/apixyz", |_req, res| res.send("other"));
+
+        let get = |path: &str| {
+            let req = Request::new(&format!("GET {} HTTP/1.1", path), HashMap::new(), "".into());
+            dispatch(&router, req)
+        };
+        assert!(get("/api").contains("X-Api: 1\r\n"));
+        assert!(get("/api/users").contains("X-Api: 1\r\n"));
+        assert!(!get("/apixyz").contains("X-Api"));
+        assert!(!get("/").contains("X-Api"));
+    }
+
+    // TEST - the first registered route wins over a later wildcard
+    #[test]
+    fn test_wildcard_precedence() {
+        let mut router = Router::new();
+        router.add_route("GET", "/files/index.html", |_req, res| res.send("index"));
+        router.add_route("GET", "/files/*", |req, res| {
+            res.send(req.param("wildcard").unwrap())
+        });
+
+        let get = |path: &str| {
+            let req = Request::new(&format!("GET {} HTTP/1.1", path), HashMap::new(), "".into());
+            dispatch(&router, req)
+        };
+        assert!(get("/files/index.html").ends_with("\r\n\r\nindex"));
+        assert!(get("/files/a/b/c.txt").ends_with("\r\n\r\na/b/c.txt"));
+    }
+
+    // TEST - static directories serve files and refuse traversal
+    #[test]
+    fn test_static_dir() {
+        let root = std::env::temp_dir().join(format!("rxpress-static-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("css")).unwrap();
+        std::fs::write(root.join("css/site.css"), "body{}").unwrap();
+
+        let mut router = Router::new();
+        router.add_static_dir("/public/", root.to_str().unwrap());
+        router.add_route("GET", "/other", |_req, res| res.send("other"));
+
+        let get = |path: &str| {
+            let req = Request::new(&format!("GET {} HTTP/1.1", path), HashMap::new(), "".into());
+            dispatch(&router, req)
+        };
+        let out = get("/public/css/site.css");
+        assert!(out.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(out.contains("Content-Type: text/css; charset=utf-8\r\n"));
+        assert!(out.ends_with("\r\n\r\nbody{}"));
+
+        assert!(get("/public/missing.css").starts_with("HTTP/1.1 404 Not Found"));
+        assert!(get("/public/css").starts_with("HTTP/1.1 404 Not Found"));
+        for attack in [
+            "/public/../../etc/passwd",
+            "/public/css/%2e%2e/%2e%2e/etc/passwd",
+            "/public/..%2f..%2fetc/passwd",
+            "/public/..%5c..%5cetc/passwd",
+        ] {
+            assert!(
+                get(attack).starts_with("HTTP/1.1 403 Forbidden"),
+                "{}",
+                attack
+            );
         }
+        assert!(get("/other").ends_with("\r\n\r\nother"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    // TEST - a standalone router dispatches into an in-memory response
+    #[test]
+    fn test_standalone_in_memory() {
+        let mut router = Router::new();
+        router.add_route("GET", "/", |_req, res| res.send("root"));
+        router.add_route("POST", "/items/:id", |req, res| {
+            res.status(201)
+                .send(&format!("{}:{}", req.param_or("id", ""), req.body));
+        });
+
+        let handle = |line: &str, body: &str| {
+            let mut req = Request::new(line, HashMap::new(), body.into());
+            let mut out: Vec<u8> = Vec::new();
+            router.handle(&mut req, &mut Response::new(&mut out));
+            String::from_utf8(out).unwrap()
+        };
+        assert!(handle("GET / HTTP/1.1", "").ends_with("\r\n\r\nroot"));
+        let out = handle("POST /items/9 HTTP/1.1", "x");
+        assert!(out.starts_with("HTTP/1.1 201 Created") && out.ends_with("\r\n\r\n9:x"));
+        assert!(handle("GET /missing HTTP/1.1", "").starts_with("HTTP/1.1 404"));
+    }
+
+    // TEST - a handler that writes nothing still produces a terminated response
+    #[test]
+    fn test_noop_handler_gets_empty_response() {
+        let mut router = Router::new();
+        router.add_route("GET", "/noop", |_req, _res| {});
+
+        let req = Request::new("GET /noop HTTP/1.1", HashMap::new(), "".into());
+        let out = dispatch(&router, req);
+        assert!(out.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(out.ends_with("Content-Length: 0\r\n\r\n"));
+    }
+
+    // TEST - a known path with the wrong method gets 405 and Allow
+    #[test]
+    fn test_method_not_allowed() {
+        let mut router = Router::new();
+        router.add_route("POST", "/users", |_req, res| res.send("created"));
+        router.add_route("GET", "/users/:id", |_req, res| res.send("user"));
+        router.add_route("DELETE", "/users/:id", |_req, res| res.send("deleted"));
+
+        let send = |line: &str| {
+            let req = Request::new(line, HashMap::new(), "".into());
+            dispatch(&router, req)
+        };
+        let out = send("GET /users HTTP/1.1");
+        assert!(out.starts_with("HTTP/1.1 405 Method Not Allowed"));
+        assert!(out.contains("Allow: POST, OPTIONS\r\n"));
+
+        let out = send("PUT /users/7 HTTP/1.1");
+        assert!(out.starts_with("HTTP/1.1 405 Method Not Allowed"));
+        assert!(out.contains("Allow: GET, DELETE, HEAD, OPTIONS\r\n"));
+
+        assert!(send("POST /users HTTP/1.1").ends_with("\r\n\r\ncreated"));
+        assert!(send("GET /posts HTTP/1.1").starts_with("HTTP/1.1 404 Not Found"));
+    }
+
+    // TEST - trailing slashes redirect to the form a route serves, both ways
+    #[test]
+    fn test_redirect_trailing_slash() {
+        let mut router = Router::new();
+        router.add_route("GET", "/users", |_req, res| res.send("users"));
+        router.add_route("GET", "/docs/", |_req, res| res.send("docs"));
+        router.add_route("GET", "/", |_req, res| res.send("home"));
+
+        let get = |router: &Router, path: &str| {
+            let req = Request::new(&format!("GET {} HTTP/1.1", path), HashMap::new(), "".into());
+            dispatch(router, req)
+        };
+        // off by default
+        assert!(get(&router, "/users/").starts_with("HTTP/1.1 404 Not Found"));
+
+        router.redirect_trailing_slash(true);
+        let out = get(&router, "/users/?page=2&q=a%20b");
+        assert!(out.starts_with("HTTP/1.1 301 Moved Permanently"));
+        assert!(out.contains("Location: /users?page=2&q=a%20b\r\n"));
+
+        let out = get(&router, "/docs");
+        assert!(out.starts_with("HTTP/1.1 301 Moved Permanently"));
+        assert!(out.contains("Location: /docs/\r\n"));
+
+        // exact matches and unknown paths are left alone
+        assert!(get(&router, "/users").ends_with("\r\n\r\nusers"));
+        assert!(get(&router, "/").ends_with("\r\n\r\nhome"));
+        assert!(get(&router, "/posts/").starts_with("HTTP/1.1 404 Not Found"));
+    }
+
+    // TEST - OPTIONS lists a path's methods, unless a route handles it
+    #[test]
+    fn test_automatic_options() {
+        let mut router = Router::new();
+        router.add_route("GET", "/users/:id", |_req, res| res.send("user"));
+        router.add_route("DELETE", "/users/:id", |_req, res| res.send("deleted"));
+        router.add_route("POST", "/teams", |_req, res| res.send("created"));
+        router.add_route("OPTIONS", "/teams", |_req, res| res.send("custom"));
+
+        let options = |path: &str| {
+            let req = Request::new(
+                &format!("OPTIONS {} HTTP/1.1", path),
+                HashMap::new(),
+                "".into(),
+            );
+            dispatch(&router, req)
+        };
+        let out = options("/users/5");
+        assert!(out.starts_with("HTTP/1.1 204 No Content"));
+        assert!(out.contains("Allow: GET, DELETE, HEAD, OPTIONS\r\n"));
+
+        assert!(options("/teams").ends_with("\r\n\r\ncustom"));
+        assert!(options("/nothing").starts_with("HTTP/1.1 404 Not Found"));
+
+        // a registered OPTIONS route is listed once
+        let req = Request::new("GET /teams HTTP/1.1", HashMap::new(), "".into());
+        let out = dispatch(&router, req);
+        assert!(out.starts_with("HTTP/1.1 405 Method Not Allowed"));
+        assert!(out.contains("Allow: POST, OPTIONS\r\n"));
+    }
+
+    // TEST - a custom not-found handler replaces the default 404
+    #[test]
+    fn test_custom_not_found() {
+        let mut router = Router::new();
+        router.add_route("GET", "/", |_req, res| res.send("home"));
+        router.set_not_found(|req, res| {
+            res.json(&format!(r#"{{"missing":"{}"}}"#, req.path));
+        });
+
+        let req = Request::new("GET /nope HTTP/1.1", HashMap::new(), "".into());
+        let out = dispatch(&router, req);
+        assert!(out.starts_with("HTTP/1.1 404 Not Found"));
+        assert!(out.ends_with(r#"{"missing":"/nope"}"#));
+
+        let req = Request::new("GET / HTTP/1.1", HashMap::new(), "".into());
+        assert!(dispatch(&router, req).ends_with("\r\n\r\nhome"));
+    }
+
+    // TEST - a mounted router keeps its params and its middleware stays scoped
+    #[test]
+    fn test_mount() {
+        let mut users = Router::new();
+        users.add_middleware(|_req, res| {
+            res.set_header("X-Users", "1");
+            true
+        });
+        users.add_route("GET", "/", |req, res| {
+            res.send(&format!("list {}", req.param_or("org", "?")))
+        });
+        users.add_route("GET", "/:id", |req, res| {
+            res.send(&format!(
+                "{} {}",
+                req.param_or("org", "?"),
+                req.param_or("id", "?")
+            ))
+        });
+
+        let mut router = Router::new();
+        router.add_route("GET", "/", |_req, res| res.send("home"));
+        router.mount("/orgs/:org/users/", users);
+
+        let get = |path: &str| {
+            let req = Request::new(&format!("GET {} HTTP/1.1", path), HashMap::new(), "".into());
+            dispatch(&router, req)
+        };
+        let out = get("/orgs/acme/users/42");
+        assert!(out.contains("X-Users: 1\r\n") && out.ends_with("\r\n\r\nacme 42"));
+        assert!(get("/orgs/acme/users").ends_with("\r\n\r\nlist acme"));
+        assert!(get("/orgs/acme/usersx").starts_with("HTTP/1.1 404 Not Found"));
+
+        let out = get("/");
+        assert!(!out.contains("X-Users") && out.ends_with("\r\n\r\nhome"));
+    }
+
+    // TEST - default 404 follows the Accept header
+    #[test]
+    fn test_default_not_found_negotiation() {
+        let router = Router::new();
+
+        let out = dispatch(&router, req_with_accept("application/json"));
+        assert!(out.starts_with("HTTP/1.1 404 Not Found"));
+        assert!(out.contains("Content-Type: application/json"));
+        assert!(out.ends_with(r#"{"error":"404 Not Found"}"#));
 
-        res.status(HttpStatus::NotFound).send("404 Not Found");
+        let out = dispatch(
+            &router,
+            req_with_accept("text/html,application/xhtml+xml,*/*;q=0.8"),
+        );
+        assert!(out.starts_with("HTTP/1.1 404 Not Found"));
+        assert!(out.contains("Content-Type: text/plain"));
+        assert!(out.ends_with("404 Not Found"));
     }
 }
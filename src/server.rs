@@ -15,17 +15,27 @@
 //!         res.send("Hello, world!");
 //!     });
 //!
-//!     app.run(); // blocks forever
+//!     app.run_or_panic(); // blocks forever
 //! }
 //! ```
 
 use std::collections::HashMap;
-use std::io::{BufRead, BufReader, Lines, Read};
-use std::net::{TcpListener, TcpStream};
+use std::io::{self, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::Receiver;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use crate::request::Request;
-use crate::response::Response;
-use crate::router::Router;
+use crate::cors::Cors;
+use crate::error::RxError;
+use crate::parser::{self, BodyOverflow, HeaderFolding, RequestLimits};
+use crate::pool::{QueuePolicy, ThreadPool};
+use crate::request::{HttpVersion, Request};
+use crate::response::{BodyTransform, Response, ResponseHook, ResponseOptions};
+use crate::router::{EmbeddedAssets, Router};
+use crate::status::{HttpStatus, StatusClass};
 
 /// Type alias for a request handler function.
 ///
@@ -40,6 +50,80 @@ use crate::router::Router;
 /// ```
 pub type Handler = fn(&Request, &mut Response);
 
+/// Type alias for a fallible request handler, registered with [`Server::try_route`].
+///
+/// Returning `Err` sends the [`RxError`] as the response, so `?` works on any
+/// [`std::error::Error`].
+///
+/// ```no_run
+/// use rxpress::{Request, Response, RxError};
+///
+/// fn handler(req: &Request, res: &mut Response) -> Result<(), RxError> {
+///     let page: u32 = req.query_or("page", "1").parse()?;
+///     res.send(&format!("Page {}", page));
+///     Ok(())
+/// }
+/// ```
+pub type TryHandler = fn(&Request, &mut Response) -> Result<(), RxError>;
+
+/// Type alias for a check run before the request body is read, registered with
+/// [`Server::before_body`]. Returning `false` rejects the request.
+///
+/// ```no_run
+/// use rxpress::{Request, Response};
+///
+/// fn require_auth(req: &Request, res: &mut Response) -> bool {
+///     if req.header("Authorization").is_some() {
+///         return true;
+///     }
+///     res.status(401).send("Unauthorized");
+///     false
+/// }
+/// ```
+pub type BeforeBodyHook = fn(&Request, &mut Response) -> bool;
+
+/// Type alias for a protocol handler registered with [`Server::on_upgrade`],
+/// which takes over the raw connection after `101 Switching Protocols`.
+///
+/// ```no_run
+/// use std::io::{Read, Write};
+/// use std::net::TcpStream;
+/// use rxpress::Request;
+///
+/// fn echo(mut stream: TcpStream, _req: Request) {
+///     let mut buf = [0; 1024];
+///     while let Ok(n @ 1..) = stream.read(&mut buf) {
+///         let _ = stream.write_all(&buf[..n]);
+///     }
+/// }
+/// ```
+pub type UpgradeHandler = fn(TcpStream, Request);
+
+/// Type alias for middleware run before route matching, registered with
+/// [`Server::use_middleware`]. Returning `false` stops dispatching.
+///
+/// ```no_run
+/// use rxpress::{Request, Response};
+///
+/// fn log(req: &mut Request, _res: &mut Response) -> bool {
+///     println!("{} {}", req.method, req.path);
+///     true
+/// }
+/// ```
+pub type Middleware = fn(&mut Request, &mut Response) -> bool;
+
+/// Type alias for a side-effect-only observer of every finished request,
+/// registered with [`Server::tap`].
+///
+/// ```no_run
+/// use rxpress::{Request, Response};
+///
+/// fn access_log(req: &Request, res: &Response) {
+///     println!("{} {} -> {}", req.method, req.path, res.status_code());
+/// }
+/// ```
+pub type Tap = fn(&Request, &Response);
+
 /// A simple HTTP server for handling requests.
 ///
 /// The [`Server`] manages a [`Router`] internally, where routes are registered
@@ -54,12 +138,117 @@ pub type Handler = fn(&Request, &mut Response);
 ///     app.get("/", |_req, res| {
 ///         res.send("Hello, world!");
 ///     });
-///     app.run();
+///     app.run_or_panic();
 /// }
 /// ```
 pub struct Server {
     address: String,
     router: Router,
+    response_options: ResponseOptions,
+    cors: Option<Cors>,
+    limits: RequestLimits,
+    hosts: Vec<(String, Router)>,
+    extra_addresses: Vec<String>,
+    counters: Counters,
+    before_body: Vec<BeforeBodyHook>,
+    taps: Vec<Tap>,
+    /// Health check paths, kept out of the taps
+    quiet_paths: Vec<String>,
+    /// Protocols offered through `Upgrade`, with their handlers
+    upgrades: Vec<(String, UpgradeHandler)>,
+    /// Bind attempts and the delay between them
+    bind_retry: (u32, Duration),
+    /// Requests served on one connection before it is closed
+    max_requests: usize,
+    /// How long an idle connection waits for its next request
+    keep_alive_timeout: Duration,
+    /// How long one write may block on a client that stopped reading
+    write_timeout: Option<Duration>,
+    workers: usize,
+    queue_policy: QueuePolicy,
+    /// Raised when [`Server::run_until`]'s flag is set
+    shutdown: ShutdownSignal,
+}
+
+/// Tells long-running handlers that the server is shutting down, from
+/// [`Request::shutdown_signal`].
+///
+/// Cheap to clone; every clone observes the same signal. It only becomes ready
+/// for servers started with [`Server::run_until`].
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+/// use rxpress::{Json, Server};
+///
+/// let mut app = Server::new("3000");
+/// app.get("/events", |req, res| {
+///     let shutdown = req.shutdown_signal();
+///     let mut events = res.json_stream();
+///     // one event per second until the server stops
+///     while !shutdown.wait_timeout(Duration::from_secs(1)) {
+///         if events.push(&Json::String("tick".into())).is_err() {
+///             break; // client went away
+///         }
+///     }
+/// });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownSignal {
+    ready: Arc<AtomicBool>,
+}
+
+impl ShutdownSignal {
+    /// Returns `true` once the server has started shutting down.
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::SeqCst)
+    }
+
+    /// Waits up to `timeout` for the signal, returning `true` as soon as it is
+    /// ready and `false` if the time ran out first.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while !self.is_ready() {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return false;
+            }
+            thread::sleep(left.min(SHUTDOWN_POLL));
+        }
+        true
+    }
+
+    /// Makes the signal ready for every clone.
+    fn trigger(&self) {
+        self.ready.store(true, Ordering::SeqCst);
+    }
+
+    /// Clears the signal so a stopped server can be run again.
+    fn reset(&self) {
+        self.ready.store(false, Ordering::SeqCst);
+    }
+}
+
+/// How often shutdown flags are checked.
+const SHUTDOWN_POLL: Duration = Duration::from_millis(10);
+
+/// A snapshot of the server's runtime counters, from [`Server::stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ServerStats {
+    /// Requests handled since the server started
+    pub requests: u64,
+    /// Connections currently open
+    pub active_connections: u64,
+    /// Response body bytes written
+    pub bytes_out: u64,
+}
+
+/// Live counters behind [`ServerStats`], shared by all accept loops.
+#[derive(Default)]
+struct Counters {
+    requests: AtomicU64,
+    active_connections: AtomicU64,
+    bytes_out: AtomicU64,
 }
 
 impl Server {
@@ -82,247 +271,1543 @@ impl Server {
         Server {
             address,
             router: Router::new(),
+            response_options: ResponseOptions::new(),
+            cors: None,
+            limits: RequestLimits::default(),
+            hosts: Vec::new(),
+            extra_addresses: Vec::new(),
+            counters: Counters::default(),
+            before_body: Vec::new(),
+            taps: Vec::new(),
+            quiet_paths: Vec::new(),
+            upgrades: Vec::new(),
+            bind_retry: (1, Duration::ZERO),
+            max_requests: 100,
+            keep_alive_timeout: Duration::from_secs(5),
+            write_timeout: None,
+            workers: thread::available_parallelism().map_or(1, |n| n.get()),
+            queue_policy: QueuePolicy::default(),
+            shutdown: ShutdownSignal::default(),
         }
     }
 
-    /// Registers a handler for the GET method at the given path.
+    /// Sets the maximum length of a single request or header line, in bytes.
+    ///
+    /// Longer header lines are answered with `431 Request Header Fields Too Large`
+    /// (`414 URI Too Long` for the request line) instead of being buffered
+    /// indefinitely. Defaults to 16 KiB.
     ///
     /// # Example
     /// ```
     /// use rxpress::Server;
     ///
-    /// fn main() {
-    ///     let mut app = Server::new("3000");
-    ///     app.get("/", |_req, res| {
-    ///         res.send("Hello from rxpress with GET method!");
-    ///     });
-    /// }
+    /// let mut app = Server::new("3000");
+    /// app.max_header_line_bytes(8 * 1024);
     /// ```
-    pub fn get(&mut self, path: &str, handler: Handler) {
-        self.router.add_route("GET", path, handler);
+    pub fn max_header_line_bytes(&mut self, bytes: usize) {
+        self.limits.max_line_bytes = bytes;
     }
 
-    /// Registers a handler for the POST method at the given path.
+    /// Sets the largest request body accepted, in bytes, whatever its framing.
+    ///
+    /// Bodies declaring a larger `Content-Length` are answered with
+    /// `413 Payload Too Large` before any of them is read or buffered, and a
+    /// chunked body is cut off with `413` once it grows past the limit.
+    /// Defaults to 1 MiB.
     ///
     /// # Example
     /// ```
     /// use rxpress::Server;
     ///
-    /// fn main() {
-    ///     let mut app = Server::new("3000");
-    ///     app.post("/submit", |_req, res| {
-    ///         res.json(r#"{"status":"ok"}"#);
-    ///     });
-    /// }
+    /// let mut app = Server::new("3000");
+    /// app.max_body_size(10 * 1024 * 1024);
     /// ```
-    pub fn post(&mut self, path: &str, handler: Handler) {
-        self.router.add_route("POST", path, handler);
+    pub fn max_body_size(&mut self, bytes: usize) {
+        self.limits.max_body_bytes = bytes;
+        self.limits.max_total_body = bytes;
     }
 
-    /// Registers a handler for the PUT method at the given path.
+    /// Chooses how folded header lines (continuations starting with a space or
+    /// tab) are handled: rejected with `400 Bad Request` (the default) or
+    /// unfolded into the previous header's value.
     ///
     /// # Example
     /// ```
     /// use rxpress::Server;
+    /// use rxpress::parser::HeaderFolding;
     ///
-    /// fn main() {
-    ///     let mut app = Server::new("3000");
-    ///     app.put("/update", |_req, res| {
-    ///         res.send("Resource updated");
-    ///     });
-    /// }
+    /// let mut app = Server::new("3000");
+    /// app.header_folding(HeaderFolding::Unfold); // for old clients
     /// ```
-    pub fn put(&mut self, path: &str, handler: Handler) {
-        self.router.add_route("PUT", path, handler);
+    pub fn header_folding(&mut self, folding: HeaderFolding) {
+        self.limits.header_folding = folding;
     }
 
-    /// Registers a handler for the DELETE method at the given path.
+    /// Chooses what happens to a body over [`Server::max_body_size`]: answered
+    /// with `413 Payload Too Large` (the default), or cut at the limit and
+    /// handed to the handler with [`Request::body_truncated`] set.
     ///
     /// # Example
     /// ```
     /// use rxpress::Server;
+    /// use rxpress::parser::BodyOverflow;
     ///
-    /// fn main() {
-    ///     let mut app = Server::new("3000");
-    ///     app.delete("/remove", |_req, res| {
-    ///         res.send("Deleted!");
-    ///     });
-    /// }
+    /// let mut app = Server::new("3000");
+    /// app.body_overflow(BodyOverflow::Truncate);
     /// ```
-    pub fn delete(&mut self, path: &str, handler: Handler) {
-        self.router.add_route("DELETE", path, handler);
+    pub fn body_overflow(&mut self, overflow: BodyOverflow) {
+        self.limits.body_overflow = overflow;
     }
 
-    /// Registers a handler for the PATCH method at the given path.
+    /// Replaces all request parsing limits at once: line length, URI length,
+    /// header count and size, and body size.
+    ///
+    /// Requests over a limit are answered with `414`, `431`, or `413`.
     ///
     /// # Example
     /// ```
     /// use rxpress::Server;
+    /// use rxpress::parser::RequestLimits;
     ///
-    /// fn main() {
-    ///     let mut app = Server::new("3000");
-    ///     app.patch("/modify", |_req, res| {
-    ///         res.send("Patched!");
-    ///     });
-    /// }
+    /// let mut app = Server::new("3000");
+    /// app.limits(RequestLimits {
+    ///     max_header_count: 32,
+    ///     max_body_bytes: 10 * 1024 * 1024,
+    ///     ..RequestLimits::default()
+    /// });
     /// ```
-    pub fn patch(&mut self, path: &str, handler: Handler) {
-        self.router.add_route("PATCH", path, handler);
+    pub fn limits(&mut self, limits: RequestLimits) {
+        self.limits = limits;
     }
 
-    /// Registers a handler for the OPTIONS method at the given path.
+    /// Appends a trailing `\n` to every `send()` body that doesn't already end with one.
+    ///
+    /// Handy for POSIX-friendly `curl` output. Disabled by default.
     ///
     /// # Example
     /// ```
     /// use rxpress::Server;
     ///
-    /// fn main() {
-    ///     let mut app = Server::new("3000");
-    ///     app.options("/any", |_req, res| {
-    ///         res.set_header("Allow", "GET, POST, OPTIONS");
-    ///         res.send("Allowed methods listed");
-    ///     });
-    /// }
+    /// let mut app = Server::new("3000");
+    /// app.text_trailing_newline(true);
     /// ```
-    pub fn options(&mut self, path: &str, handler: Handler) {
-        self.router.add_route("OPTIONS", path, handler);
+    pub fn text_trailing_newline(&mut self, enabled: bool) {
+        self.response_options.trailing_newline = enabled;
     }
 
-    /// Registers a handler for the HEAD method at the given path.
+    /// Overrides the reason phrases sent in the status line for specific codes.
+    ///
+    /// Codes not present in `map` keep their standard [`HttpStatus::reason`](crate::HttpStatus::reason).
+    /// A reason passed explicitly via `res.status((code, reason))` always wins.
     ///
     /// # Example
     /// ```
+    /// use std::collections::HashMap;
     /// use rxpress::Server;
     ///
-    /// fn main() {
-    ///     let mut app = Server::new("3000");
-    ///     app.head("/check", |_req, res| {
-    ///         res.set_header("Content-Length", "0");
-    ///     });
-    /// }
+    /// let mut app = Server::new("3000");
+    /// app.status_reasons(HashMap::from([(404, "Nope".to_string())]));
     /// ```
-    pub fn head(&mut self, path: &str, handler: Handler) {
-        self.router.add_route("HEAD", path, handler);
+    pub fn status_reasons(&mut self, map: HashMap<u16, String>) {
+        self.response_options.status_reasons = Some(map);
     }
 
-    /// Starts listening for incoming TCP connections.
+    /// Controls whether error bodies include internal details such as file paths.
+    ///
+    /// Defaults to `true` in debug builds and `false` in release builds, so
+    /// production servers don't leak filesystem layout.
     ///
     /// # Example
-    /// ```no_run
+    /// ```
     /// use rxpress::Server;
     ///
-    /// fn main() {
-    ///     let mut app = Server::new("3000");
-    ///     app.get("/", |_req, res| {
-    ///         res.send("Hello world!");
-    ///     });
+    /// let mut app = Server::new("3000");
+    /// app.debug_errors(false);
+    /// ```
+    pub fn debug_errors(&mut self, enabled: bool) {
+        self.response_options.debug_errors = enabled;
+    }
+
+    /// Registers a hook run right before any response of `class` is written.
     ///
-    ///     app.run(); // blocks forever
-    /// }
+    /// Hooks can still add or change headers. Several hooks may be registered,
+    /// and they run in registration order.
+    ///
+    /// # Example
     /// ```
+    /// use rxpress::{Server, StatusClass};
     ///
-    /// This function will block the current thread until the server is stopped.
-    pub fn run(&self) {
-        let listener = TcpListener::bind(&self.address).expect("Failed to bind port");
-
-        println!("[rxpress] running on http://{} ⚙️", self.address);
-
-        for stream in listener.incoming() {
-            match stream {
-                Ok(stream) => self.handle_connection(stream),
-                Err(err) => eprintln!("Connection failed: {}", err),
-            }
-        }
+    /// let mut app = Server::new("3000");
+    /// app.on_status_class(StatusClass::ClientError, |res| {
+    ///     res.set_header("X-Error", "true");
+    /// });
+    /// app.on_status_class(StatusClass::ServerError, |res| {
+    ///     res.set_header("X-Error", "true");
+    /// });
+    /// ```
+    pub fn on_status_class(&mut self, class: StatusClass, hook: ResponseHook) {
+        self.response_options.status_hooks.push((class, hook));
     }
 
-    /// Returns the server's full address (`127.0.0.1:<port>`).
-    pub fn address(&self) -> &str {
-        &self.address
+    /// Sets the `Content-Language` sent on every response that doesn't set its
+    /// own with [`Response::content_language`].
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.default_language("en-US");
+    /// ```
+    pub fn default_language(&mut self, lang: &str) {
+        self.response_options.default_language = Some(lang.to_string());
     }
 
-    /* ---- Private Functions ---- */
-    // Handles an incoming client connection.
+    /// Controls whether file responses advertise `Accept-Ranges: bytes`.
     ///
-    /// Reads a basic HTTP request and responds with a static message.
-    fn handle_connection(&self, mut stream: TcpStream) {
-        let mut buf_reader = BufReader::new(&stream);
-        let mut lines = buf_reader.by_ref().lines();
-
-        //Request URL
-        let request_line = self.get_request_line(&mut lines);
-        // println!("[request] {}", request_line);
-
-        //Request Headers
-        let headers = self.get_headers(&mut lines);
-        // println!("[headers] {:?}", headers);
-
-        // Body
-        let body = self.get_body(&headers, &mut buf_reader);
-        // println!("[body] {}", body);
-
-        let mut req = Request::new(&request_line, headers, body);
-        let mut res = Response::new(&mut stream);
-
-        // res.send("Hello from rxpress server!");
-        // res.json(r#"{"message":"hello world"}"#);
+    /// Enabled by default; turn it off when files are served from sources that
+    /// can't be resumed.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.accept_ranges(false);
+    /// ```
+    pub fn accept_ranges(&mut self, enabled: bool) {
+        self.response_options.accept_ranges = enabled;
+    }
 
-        self.router.handle(&mut req, &mut res);
+    /// Installs a transformer that rewrites every buffered response body before it
+    /// is written, e.g. to minify HTML or inject a script tag.
+    ///
+    /// `Content-Length` reflects the transformed body. Streamed bodies
+    /// ([`Response::send_reader`], [`Response::send_file`]) are passed through as is.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.transform_body(|_req, body, content_type| {
+    ///     if content_type.starts_with("text/html") {
+    ///         let html = String::from_utf8_lossy(body);
+    ///         html.replace("</body>", "<script src=\"/live.js\"></script></body>").into_bytes()
+    ///     } else {
+    ///         body.to_vec()
+    ///     }
+    /// });
+    /// ```
+    pub fn transform_body(&mut self, transform: BodyTransform) {
+        self.response_options.body_transform = Some(transform);
     }
 
-    // get HTTP request(method, path, version)
-    fn get_request_line(&self, lines: &mut Lines<&mut BufReader<&TcpStream>>) -> String {
-        lines.next().unwrap().unwrap()
+    /// Registers a check run once the headers are parsed but before the body is
+    /// read, e.g. for auth or rate limiting.
+    ///
+    /// Hooks run in registration order. When one returns `false` the request is
+    /// not dispatched: whatever it sent is the response (an empty one if nothing),
+    /// and the connection is closed without reading the body.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.before_body(|req, res| {
+    ///     if req.header("Authorization").is_none() {
+    ///         res.status(401).send("Unauthorized");
+    ///         return false;
+    ///     }
+    ///     true
+    /// });
+    /// ```
+    pub fn before_body(&mut self, hook: BeforeBodyHook) {
+        self.before_body.push(hook);
     }
 
-    //get all headers
-    fn get_headers(
-        &self,
-        lines: &mut Lines<&mut BufReader<&TcpStream>>,
-    ) -> HashMap<String, String> {
-        let mut map: HashMap<String, String> = HashMap::new();
+    /// Registers middleware run on every fully read request before its route is
+    /// matched, e.g. for logging or an auth gate shared by all routes.
+    ///
+    /// Middleware runs in registration order, even when no route matches. When
+    /// one returns `false`, dispatching stops and whatever it sent is the
+    /// response (an empty one if nothing), so the `404` fallback is skipped too.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.use_middleware(|req, res| {
+    ///     if req.header("Authorization").is_none() {
+    ///         res.status(401).send("Unauthorized");
+    ///         return false;
+    ///     }
+    ///     true
+    /// });
+    /// ```
+    pub fn use_middleware(&mut self, middleware: Middleware) {
+        self.router.add_middleware(middleware);
+    }
 
-        while let Some(Ok(line)) = lines.next() {
-            // no header -> break the loop
-            if line.is_empty() {
-                break;
-            }
+    /// Registers middleware that only runs for paths under `prefix`, e.g. to parse
+    /// JSON bodies for `/api/*` while leaving static routes alone.
+    ///
+    /// The prefix matches whole path segments, so `/api` covers `/api` and
+    /// `/api/users` but not `/apixyz`; a trailing slash is ignored. Otherwise
+    /// this behaves like [`Server::use_middleware`], sharing its order.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::{Json, Server};
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.use_path("/api", |req, res| {
+    ///     if !req.body.is_empty() && Json::parse(&req.body).is_err() {
+    ///         res.status(400).send("Invalid JSON");
+    ///         return false;
+    ///     }
+    ///     true
+    /// });
+    /// ```
+    pub fn use_path(&mut self, prefix: &str, middleware: Middleware) {
+        self.router.add_path_middleware(prefix, middleware);
+    }
 
-            // split headers with ':' & store as key-value pair
-            if let Some((key, val)) = line.split_once(":") {
-                map.insert(
-                    key.trim().to_string().to_ascii_lowercase(), //put keys as lowercase
-                    val.trim().to_string(),
-                );
-            }
-        }
+    /// Registers a tap that observes every request once its response is sent,
+    /// e.g. for analytics or access logs.
+    ///
+    /// Unlike middleware, a tap can't change or stop anything. Taps run in
+    /// registration order for every dispatched request, matched or not (404s
+    /// included), and for requests turned away by [`Server::before_body`];
+    /// only [`Server::health_check`] paths are left out.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.tap(|req, res| {
+    ///     println!("{} {} -> {}", req.method, req.path, res.status_code());
+    /// });
+    /// ```
+    pub fn tap(&mut self, tap: Tap) {
+        self.taps.push(tap);
+    }
 
-        map
+    /// Serves a health check at `path` that answers `200 OK` with the body `ok`.
+    ///
+    /// Requests to it skip the taps registered with [`Server::tap`], so probes
+    /// polling every few seconds don't flood the access log. Use
+    /// [`Server::health_check_with`] to choose the status and body.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.health_check("/healthz");
+    /// ```
+    pub fn health_check(&mut self, path: &str) {
+        self.health_check_with(path, |_req, res| res.send("ok"));
     }
 
-    //get complete body
-    fn get_body(
-        &self,
-        headers: &HashMap<String, String>,
-        buf_reader: &mut BufReader<&TcpStream>,
-    ) -> String {
-        let mut str = String::new();
-
-        // find the body with 'content-length' key
-        if let Some(len) = headers.get("content-length") {
-            if let Ok(size) = len.parse::<usize>() {
-                let mut buffer = vec![0; size];
-                buf_reader.read_exact(&mut buffer).unwrap();
-                str = String::from_utf8_lossy(&buffer).to_string();
-            }
-        }
+    /// Like [`Server::health_check`], but `handler` builds the response, e.g. to
+    /// answer `503` while a dependency is down.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use rxpress::Server;
+    ///
+    /// static DB_UP: AtomicBool = AtomicBool::new(true);
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.health_check_with("/readyz", |_req, res| {
+    ///     if DB_UP.load(Ordering::Relaxed) {
+    ///         res.json(r#"{"db":"up"}"#);
+    ///     } else {
+    ///         res.status(503).json(r#"{"db":"down"}"#);
+    ///     }
+    /// });
+    /// ```
+    pub fn health_check_with(&mut self, path: &str, handler: Handler) {
+        self.router.add_route("GET", path, handler);
+        self.quiet_paths.push(path.to_string());
+    }
 
-        str
+    /// Hands connections that ask to switch to `protocol` over to `handler`.
+    ///
+    /// An HTTP/1.1 request with `Connection: Upgrade` and an `Upgrade` header
+    /// naming `protocol` (case-insensitive) is answered with
+    /// `101 Switching Protocols`, and `handler` then owns the raw stream, with
+    /// no read timeout. Middleware, routes, and taps are skipped for it; the
+    /// request ([`Request::body`] included) is passed along. Clients must wait
+    /// for the `101` before speaking the new protocol. Upgrades are only
+    /// available over TCP, not [`Server::run_unix`].
+    ///
+    /// WebSockets or `h2c` can be built on this.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io::Write;
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.on_upgrade("chat/1", |mut stream, req| {
+    ///     let _ = writeln!(stream, "welcome to {}", req.path);
+    /// });
+    /// ```
+    pub fn on_upgrade(&mut self, protocol: &str, handler: UpgradeHandler) {
+        self.upgrades.push((protocol.to_string(), handler));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Sets how many requests one persistent connection may carry before the
+    /// server answers with `Connection: close`. Defaults to 100; `1` turns
+    /// keep-alive off.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.max_requests_per_connection(1000);
+    /// ```
+    pub fn max_requests_per_connection(&mut self, n: usize) {
+        self.max_requests = n.max(1);
+    }
+
+    /// Sets how long a kept-alive connection may sit idle (or stall mid-request)
+    /// before the server closes it, so slow clients can't hold a worker forever.
+    /// Defaults to 5 seconds; must be non-zero.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.keep_alive_timeout(Duration::from_secs(15));
+    /// ```
+    pub fn keep_alive_timeout(&mut self, timeout: Duration) {
+        self.keep_alive_timeout = timeout;
+    }
+
+    /// Gzips buffered response bodies of at least `min_size` bytes for clients
+    /// whose `Accept-Encoding` allows it (`gzip` feature).
+    ///
+    /// Only text and structured types (`text/*`, JSON, JavaScript, XML, SVG) are
+    /// compressed; streamed bodies, files sent with [`Response::send_file`],
+    /// range responses, and bodies that already have a `Content-Encoding` are
+    /// left alone. Compressed responses carry `Content-Encoding: gzip` and
+    /// `Content-Length` is the compressed size; eligible ones get
+    /// `Vary: Accept-Encoding` either way.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "gzip")]
+    /// # {
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.enable_compression(1024); // tiny bodies aren't worth it
+    /// # }
+    /// ```
+    #[cfg(feature = "gzip")]
+    pub fn enable_compression(&mut self, min_size: usize) {
+        self.response_options.compress_min = Some(min_size);
+    }
+
+    /// Sets how long a single write may block on a client that stopped reading.
+    ///
+    /// When it runs out, streaming methods such as
+    /// [`Response::send_reader`] return an error instead of waiting forever, and
+    /// the connection is closed. Off by default; must be non-zero.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.write_timeout(Duration::from_secs(30));
+    /// ```
+    pub fn write_timeout(&mut self, timeout: Duration) {
+        self.write_timeout = Some(timeout);
+    }
+
+    /// Retries binding each port up to `attempts` times, `delay` apart, before
+    /// giving up, e.g. while a container's previous instance releases the port.
+    /// Defaults to a single attempt.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.bind_retry(10, Duration::from_millis(500));
+    /// ```
+    pub fn bind_retry(&mut self, attempts: u32, delay: Duration) {
+        self.bind_retry = (attempts.max(1), delay);
+    }
+
+    /// Serves connections on a pool of `n` worker threads.
+    ///
+    /// Defaults to the number of CPUs. With `0`, each accept loop handles its
+    /// connections itself, one after another.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.workers(4);
+    /// ```
+    pub fn workers(&mut self, n: usize) {
+        self.workers = n;
+    }
+
+    /// Chooses what happens when all workers are busy and the queue is full:
+    /// block the accept loop, answer `503`, or keep queueing.
+    ///
+    /// Defaults to [`QueuePolicy::Block(64)`](QueuePolicy::Block). Only used
+    /// together with [`Server::workers`].
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    /// use rxpress::pool::QueuePolicy;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.workers(4);
+    /// app.queue_policy(QueuePolicy::Reject(32));
+    /// ```
+    pub fn queue_policy(&mut self, policy: QueuePolicy) {
+        self.queue_policy = policy;
+    }
+
+    /// Installs a CORS policy applied to every response.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    /// use rxpress::cors::{AllowOrigin, Cors};
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.cors(Cors::new(AllowOrigin::Mirror));
+    /// ```
+    pub fn cors(&mut self, cors: Cors) {
+        self.cors = Some(cors);
+    }
+
+    /// Registers a handler for the GET method at the given path.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// fn main() {
+    ///     let mut app = Server::new("3000");
+    ///     app.get("/", |_req, res| {
+    ///         res.send("Hello from rxpress with GET method!");
+    ///     });
+    /// }
+    /// ```
+    pub fn get(&mut self, path: &str, handler: Handler) {
+        self.router.add_route("GET", path, handler);
+    }
+
+    /// Registers a handler for the POST method at the given path.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// fn main() {
+    ///     let mut app = Server::new("3000");
+    ///     app.post("/submit", |_req, res| {
+    ///         res.json(r#"{"status":"ok"}"#);
+    ///     });
+    /// }
+    /// ```
+    pub fn post(&mut self, path: &str, handler: Handler) {
+        self.router.add_route("POST", path, handler);
+    }
+
+    /// Registers a handler for the PUT method at the given path.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// fn main() {
+    ///     let mut app = Server::new("3000");
+    ///     app.put("/update", |_req, res| {
+    ///         res.send("Resource updated");
+    ///     });
+    /// }
+    /// ```
+    pub fn put(&mut self, path: &str, handler: Handler) {
+        self.router.add_route("PUT", path, handler);
+    }
+
+    /// Registers a handler for the DELETE method at the given path.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// fn main() {
+    ///     let mut app = Server::new("3000");
+    ///     app.delete("/remove", |_req, res| {
+    ///         res.send("Deleted!");
+    ///     });
+    /// }
+    /// ```
+    pub fn delete(&mut self, path: &str, handler: Handler) {
+        self.router.add_route("DELETE", path, handler);
+    }
+
+    /// Registers a handler for the PATCH method at the given path.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// fn main() {
+    ///     let mut app = Server::new("3000");
+    ///     app.patch("/modify", |_req, res| {
+    ///         res.send("Patched!");
+    ///     });
+    /// }
+    /// ```
+    pub fn patch(&mut self, path: &str, handler: Handler) {
+        self.router.add_route("PATCH", path, handler);
+    }
+
+    /// Registers a handler for the OPTIONS method at the given path.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// fn main() {
+    ///     let mut app = Server::new("3000");
+    ///     app.options("/any", |_req, res| {
+    ///         res.set_header("Allow", "GET, POST, OPTIONS");
+    ///         res.send("Allowed methods listed");
+    ///     });
+    /// }
+    /// ```
+    pub fn options(&mut self, path: &str, handler: Handler) {
+        self.router.add_route("OPTIONS", path, handler);
+    }
+
+    /// Registers a handler for the HEAD method at the given path.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// fn main() {
+    ///     let mut app = Server::new("3000");
+    ///     app.head("/check", |_req, res| {
+    ///         res.set_header("Content-Length", "0");
+    ///     });
+    /// }
+    /// ```
+    pub fn head(&mut self, path: &str, handler: Handler) {
+        self.router.add_route("HEAD", path, handler);
+    }
+
+    /// Registers a handler for every method at the given path.
+    ///
+    /// A single catch-all route is stored, whatever the number of methods. Like
+    /// any route, it only sees requests no earlier route matched.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// fn main() {
+    ///     let mut app = Server::new("3000");
+    ///     app.all("/api/*", |req, res| {
+    ///         res.status(501).send(&format!("{} is not implemented", req.method));
+    ///     });
+    /// }
+    /// ```
+    pub fn all(&mut self, path: &str, handler: Handler) {
+        self.router.add_any_route(path, handler);
+    }
+
+    /// Redirects requests whose path only differs from a route by a trailing
+    /// slash to the canonical form with `301 Moved Permanently`, keeping the
+    /// query string: `/users/` goes to `/users` when only `/users` is
+    /// registered, and `/docs` to `/docs/` when only `/docs/` is. Off by
+    /// default, leaving such requests unmatched.
+    ///
+    /// Routers added with [`Server::host_route`] keep their own setting (see
+    /// [`Router::redirect_trailing_slash`]).
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.redirect_trailing_slash(true);
+    /// app.get("/users", |_req, res| res.send("users")); // GET /users/ -> 301 /users
+    /// ```
+    pub fn redirect_trailing_slash(&mut self, enabled: bool) {
+        self.router.redirect_trailing_slash(enabled);
+    }
+
+    /// Registers the handler used when no route matches, e.g. for a branded
+    /// error page. The response status is `404` unless the handler changes it.
+    ///
+    /// Without one, a plain text (or JSON, if preferred by `Accept`)
+    /// `404 Not Found` is sent. Routers added with [`Server::host_route`] keep
+    /// their own (see [`Router::set_not_found`]).
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.not_found(|req, res| {
+    ///     res.html(&format!("<h1>Nothing at {}</h1>", req.path));
+    /// });
+    /// ```
+    pub fn not_found(&mut self, handler: Handler) {
+        self.router.set_not_found(handler);
+    }
+
+    /// Serves files embedded in the binary under `prefix`, with MIME types
+    /// inferred from their paths. No filesystem access is involved.
+    ///
+    /// Paths missing from the map fall through to the other routes, ending in
+    /// a `404` when nothing else matches.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// static ASSETS: &[(&str, &[u8])] = &[
+    ///     ("app.js", b"console.log('hi');"),
+    ///     ("index.html", b"<h1>Hello</h1>"),
+    /// ];
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.static_embedded("/static", ASSETS); // GET /static/app.js
+    /// ```
+    pub fn static_embedded(&mut self, prefix: &str, assets: EmbeddedAssets) {
+        self.router.add_embedded(prefix, assets);
+    }
+
+    /// Serves files from the `fs_root` directory for `GET` and `HEAD` requests
+    /// under `url_prefix`, guessing each `Content-Type` from the extension.
+    ///
+    /// `GET /static/css/site.css` sends `<fs_root>/css/site.css` (see
+    /// [`Response::file`]). Missing files get a `404`, and paths containing
+    /// `..` segments, even percent-encoded, get a `403` so requests can't
+    /// escape `fs_root`. Static directories are checked before the routes.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.static_dir("/static", "public"); // GET /static/app.js -> public/app.js
+    /// ```
+    pub fn static_dir(&mut self, url_prefix: &str, fs_root: &str) {
+        self.router.add_static_dir(url_prefix, fs_root);
+    }
+
+    /// Registers a fallible handler for any method at the given path.
+    ///
+    /// If the handler returns `Err`, the [`RxError`] is sent with its status code.
+    /// Its message is only shown when [`Server::debug_errors`] is enabled.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// fn main() {
+    ///     let mut app = Server::new("3000");
+    ///     app.try_route("GET", "/users/:id", |req, res| {
+    ///         let id: u32 = req.param_or("id", "").parse()?;
+    ///         res.send(&format!("User {}", id));
+    ///         Ok(())
+    ///     });
+    /// }
+    /// ```
+    pub fn try_route(&mut self, method: &str, path: &str, handler: TryHandler) {
+        self.router.add_try_route(method, path, handler);
+    }
+
+    /// Mounts the routes, middleware, and assets of `router` under `prefix`, so
+    /// route groups can be built in their own modules (see [`Router::mount`]).
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::{Router, Server};
+    ///
+    /// let mut v1 = Router::new();
+    /// v1.add_route("GET", "/users/:id", |req, res| res.send(req.param_or("id", "?")));
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.mount("/api/v1", v1); // serves GET /api/v1/users/:id
+    /// ```
+    pub fn mount(&mut self, prefix: &str, router: Router) {
+        self.router.mount(prefix, router);
+    }
+
+    /// Routes requests whose `Host` header matches `host` to a separate [`Router`].
+    ///
+    /// The port in the `Host` header is ignored and matching is case-insensitive.
+    /// Requests for unknown hosts fall back to the routes registered on the server.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    /// use rxpress::router::Router;
+    ///
+    /// let mut api = Router::new();
+    /// api.add_route("GET", "/", |_req, res| res.json(r#"{"api":true}"#));
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.host_route("api.example.com", api);
+    /// app.get("/", |_req, res| res.send("www"));
+    /// ```
+    pub fn host_route(&mut self, host: &str, router: Router) {
+        self.hosts.push((host.to_ascii_lowercase(), router));
+    }
+
+    /// Listens on an additional port (`127.0.0.1:<port>`) with the same routes.
+    ///
+    /// Useful for serving e.g. the public site and an admin interface from one
+    /// server. [`Server::run`] spawns one accept loop per listener.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("8080");
+    /// app.add_listener(8081);
+    /// ```
+    pub fn add_listener(&mut self, port: u16) {
+        self.extra_addresses.push(format!("127.0.0.1:{}", port));
+    }
+
+    /// Starts listening for incoming TCP connections.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// fn main() {
+    ///     let mut app = Server::new("3000");
+    ///     app.get("/", |_req, res| {
+    ///         res.send("Hello world!");
+    ///     });
+    ///
+    ///     if let Err(err) = app.run() {
+    ///         eprintln!("could not start: {}", err); // e.g. address in use
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// This function will block the current thread until the server is stopped.
+    /// Every listener added with [`Server::add_listener`] gets its own accept loop,
+    /// feeding the worker pool (see [`Server::workers`]).
+    ///
+    /// Returns the error if a port can't be bound (after any
+    /// [`Server::bind_retry`] attempts). To stop the server cleanly, start it
+    /// with [`Server::run_with_shutdown`] or [`Server::run_until`].
+    pub fn run(&self) -> io::Result<()> {
+        let listeners = self.bind_all()?;
+        self.serve_listeners(&listeners);
+        Ok(())
+    }
+
+    /// Like [`Server::run`], but panics if a port can't be bound.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// fn main() {
+    ///     let mut app = Server::new("3000");
+    ///     app.get("/", |_req, res| res.send("Hello world!"));
+    ///     app.run_or_panic(); // blocks forever
+    /// }
+    /// ```
+    pub fn run_or_panic(&self) {
+        if let Err(err) = self.run() {
+            panic!("Failed to bind port: {}", err);
+        }
+    }
+
+    /// Like [`Server::run`], but stops once `stop` is set.
+    ///
+    /// New connections are no longer accepted, and handlers still running see
+    /// [`Request::shutdown_signal`] become ready so streaming loops can wrap up.
+    /// Requests in flight finish and their connections close; an idle
+    /// keep-alive connection is dropped once [`Server::keep_alive_timeout`]
+    /// runs out. Returns when every connection is done.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use rxpress::Server;
+    ///
+    /// static STOP: AtomicBool = AtomicBool::new(false);
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.post("/shutdown", |_req, res| {
+    ///     STOP.store(true, Ordering::SeqCst);
+    ///     res.send("Bye!");
+    /// });
+    /// app.run_until(&STOP).unwrap();
+    /// ```
+    pub fn run_until(&self, stop: &AtomicBool) -> io::Result<()> {
+        let listeners = self.bind_all()?;
+        self.serve(&listeners, Some(stop));
+        Ok(())
+    }
+
+    /// Like [`Server::run_until`], but stops when a message arrives on `shutdown`,
+    /// or once every sender for it is dropped.
+    ///
+    /// The accept loops stop and requests in flight finish before this returns,
+    /// so the ports are free again afterwards.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use std::sync::mpsc;
+    /// use std::thread;
+    /// use rxpress::Server;
+    ///
+    /// let (stop, shutdown) = mpsc::channel();
+    /// let server = thread::spawn(move || {
+    ///     let mut app = Server::new("3000");
+    ///     app.get("/", |_req, res| res.send("Hello!"));
+    ///     app.run_with_shutdown(shutdown)
+    /// });
+    ///
+    /// // ... later, e.g. on Ctrl-C or before a restart
+    /// stop.send(()).unwrap();
+    /// server.join().unwrap().unwrap();
+    /// ```
+    pub fn run_with_shutdown(&self, shutdown: Receiver<()>) -> io::Result<()> {
+        let listeners = self.bind_all()?;
+        let stop = AtomicBool::new(false);
+
+        thread::scope(|scope| {
+            let stop = &stop;
+            scope.spawn(move || {
+                let _ = shutdown.recv(); // a message, or every sender gone
+                stop.store(true, Ordering::SeqCst);
+            });
+            self.serve(&listeners, Some(stop));
+        });
+        Ok(())
+    }
+
+    /// Serves already bound listeners, on the worker pool if there is one.
+    pub(crate) fn serve_listeners(&self, listeners: &[TcpListener]) {
+        self.serve(listeners, None);
+    }
+
+    /// Starts listening on a unix domain socket at `path` instead of TCP.
+    ///
+    /// A stale socket file left behind by a previous run is removed before binding.
+    /// Returns the error if the socket can't be bound, like [`Server::run`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use rxpress::Server;
+    ///
+    /// fn main() {
+    ///     let mut app = Server::new("0");
+    ///     app.get("/", |_req, res| {
+    ///         res.send("Hello over IPC!");
+    ///     });
+    ///
+    ///     if let Err(err) = app.run_unix("/tmp/rxpress.sock") {
+    ///         eprintln!("could not start: {}", err);
+    ///     }
+    /// }
+    /// ```
+    #[cfg(unix)]
+    pub fn run_unix(&self, path: &str) -> io::Result<()> {
+        use std::os::unix::fs::FileTypeExt;
+        use std::os::unix::net::UnixListener;
+
+        // only clean up sockets, never regular files
+        if let Ok(meta) = std::fs::metadata(path)
+            && meta.file_type().is_socket()
+        {
+            let _ = std::fs::remove_file(path);
+        }
+
+        let listener = UnixListener::bind(path)?;
+        println!("[rxpress] running on unix:{} ⚙️", path);
+
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => self.handle_connection(stream),
+                Err(err) => eprintln!("Connection failed: {}", err),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a snapshot of the runtime counters: requests served, open
+    /// connections, and response body bytes written.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// let app = Server::new("3000");
+    /// assert_eq!(app.stats().requests, 0);
+    /// ```
+    pub fn stats(&self) -> ServerStats {
+        ServerStats {
+            requests: self.counters.requests.load(Ordering::Relaxed),
+            active_connections: self.counters.active_connections.load(Ordering::Relaxed),
+            bytes_out: self.counters.bytes_out.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Checks that every registered `GET` route answers `HEAD` with the same
+    /// status line and headers, and without a body.
+    ///
+    /// Each route is called once per method with a synthetic request (params
+    /// are filled with `1`), so handlers with side effects will see them. Meant
+    /// for tests and other dev-time checks.
+    ///
+    /// # Panics
+    /// Panics on the first route whose `HEAD` response differs from its `GET`.
+    ///
+    /// # Example
+    /// ```
+    /// use rxpress::Server;
+    ///
+    /// let mut app = Server::new("3000");
+    /// app.get("/users/:id", |req, res| {
+    ///     res.send(&format!("user {}", req.param("id").unwrap()));
+    /// });
+    ///
+    /// app.assert_head_get_consistency();
+    /// ```
+    pub fn assert_head_get_consistency(&self) {
+        let routers = std::iter::once((None, &self.router)).chain(
+            self.hosts
+                .iter()
+                .map(|(host, router)| (Some(host.as_str()), router)),
+        );
+
+        for (host, router) in routers {
+            for route in router
+                .routes()
+                .iter()
+                .filter(|route| route.method.as_str() == "GET")
+            {
+                let path = route.sample_path();
+                let get = self.synthetic_request("GET", &path, host);
+                let head = self.synthetic_request("HEAD", &path, host);
+
+                let end = |raw: &[u8]| {
+                    raw.windows(4)
+                        .position(|w| w == b"\r\n\r\n")
+                        .map_or(raw.len(), |i| i + 4)
+                };
+                let (get_head, head_head) = (&get[..end(&get)], &head[..end(&head)]);
+                assert_eq!(
+                    String::from_utf8_lossy(get_head),
+                    String::from_utf8_lossy(head_head),
+                    "HEAD {} answered with different headers than GET",
+                    path
+                );
+                assert!(
+                    head.len() == head_head.len(),
+                    "HEAD {} answered with a body",
+                    path
+                );
+            }
+        }
+    }
+
+    /// Returns the server's full address (`127.0.0.1:<port>`).
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /* ---- Private Functions ---- */
+    // Handles an incoming client connection.
+    ///
+    /// A single [`BufReader`] lives for the whole connection, so bytes of a
+    /// pipelined request that were prefetched together with the previous one
+    /// are not lost. Requests already buffered are served in order; once the
+    /// buffer is drained (or the client asks to close) the connection ends.
+    fn handle_connection<S: Connection>(&self, stream: S) {
+        let active = &self.counters.active_connections;
+        active.fetch_add(1, Ordering::Relaxed);
+        self.serve_connection(stream);
+        active.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    // the request loop of `handle_connection`
+    fn serve_connection<S: Connection>(&self, mut stream: S) {
+        let read_half = match stream.try_clone() {
+            Ok(read_half) => read_half,
+            Err(err) => {
+                eprintln!("Connection failed: {}", err);
+                return;
+            }
+        };
+        let mut buf_reader = BufReader::new(read_half);
+        let limits = &self.limits;
+        // an idle keep-alive connection reads as closed once this runs out
+        stream.set_read_timeout(self.keep_alive_timeout);
+        if let Some(timeout) = self.write_timeout {
+            stream.set_write_timeout(timeout);
+        }
+        let tcp = stream.as_tcp();
+        let local_addr = tcp.and_then(|tcp| tcp.local_addr().ok());
+        let peer_addr = tcp.and_then(|tcp| tcp.peer_addr().ok());
+
+        for served in 1.. {
+            //Request URL
+            let request_line = match parser::get_request_line(&mut buf_reader, limits) {
+                Ok(Some(line)) => line,
+                Ok(None) => break, // client closed the connection
+                Err(status) => {
+                    self.reject(&mut stream, &mut buf_reader, status);
+                    break;
+                }
+            };
+            // println!("[request] {}", request_line);
+
+            //Request Headers
+            let headers = match parser::get_headers(&mut buf_reader, limits) {
+                Ok(headers) => headers,
+                Err(status) => {
+                    self.reject(&mut stream, &mut buf_reader, status);
+                    break;
+                }
+            };
+            // println!("[headers] {:?}", headers);
+
+            let mut req = Request::new(&request_line, headers, String::new());
+            req.set_body_limit(limits.max_body_bytes.min(limits.max_total_body));
+            req.set_shutdown_signal(&self.shutdown);
+            req.set_addrs(local_addr, peer_addr);
+            // HTTP/1.1 stays open unless asked to close, HTTP/1.0 only when asked
+            let close = served >= self.max_requests
+                || self.shutdown.is_ready()
+                || match req.http_version() {
+                    HttpVersion::Http11 => req.header_eq_ignore_case("Connection", "close"),
+                    _ => !req.header_eq_ignore_case("Connection", "keep-alive"),
+                };
+
+            // `Connection: Upgrade` hands the raw stream to a protocol handler
+            if let Some((protocol, handler)) = self.upgrade_for(&req)
+                && stream.as_tcp().is_some()
+            {
+                req.body = match parser::read_body(&mut buf_reader, &req.headers, limits) {
+                    Ok((body, truncated)) => {
+                        req.set_body_truncated(truncated);
+                        body
+                    }
+                    Err(status) => {
+                        self.reject(&mut stream, &mut buf_reader, status);
+                        break;
+                    }
+                };
+                self.counters.requests.fetch_add(1, Ordering::Relaxed);
+                if let Some(Ok(tcp)) = stream.as_tcp().map(TcpStream::try_clone) {
+                    Self::upgrade(tcp, protocol, handler, req);
+                }
+                break;
+            }
+
+            let mut res = Response::new(&mut stream);
+            res.set_version(req.http_version())
+                .set_options(&self.response_options)
+                .set_header("Connection", if close { "close" } else { "keep-alive" });
+            if req.method.eq_ignore_ascii_case("HEAD") {
+                res.omit_body();
+            }
+            #[cfg(feature = "gzip")]
+            res.set_accept_gzip(req.accepts_encoding("gzip"));
+
+            // res.send("Hello from rxpress server!");
+            // res.json(r#"{"message":"hello world"}"#);
+
+            if let Some(cors) = &self.cors {
+                cors.apply(&req, &mut res);
+            }
+
+            // pre-body checks may turn the request away before its body is read
+            if !self.before_body.iter().all(|hook| hook(&req, &mut res)) {
+                if !res.is_sent() {
+                    res.set_header("Connection", "close").end();
+                }
+                self.run_taps(&req, &res);
+                self.close_unread(&mut stream, &mut buf_reader);
+                break;
+            }
+
+            // Body
+            req.body = match parser::read_body(&mut buf_reader, &req.headers, limits) {
+                Ok((body, truncated)) => {
+                    req.set_body_truncated(truncated);
+                    body
+                }
+                Err(status) => {
+                    self.reject(&mut stream, &mut buf_reader, status);
+                    break;
+                }
+            };
+            // println!("[body] {}", req.body);
+
+            self.dispatch(&mut req, &mut res);
+            self.run_taps(&req, &res);
+
+            self.counters.requests.fetch_add(1, Ordering::Relaxed);
+            self.counters
+                .bytes_out
+                .fetch_add(res.bytes_written() as u64, Ordering::Relaxed);
+            let close = close
+                || self.shutdown.is_ready()
+                || res.write_failed()
+                || res
+                    .get_header("Connection")
+                    .is_some_and(|c| c.eq_ignore_ascii_case("close"));
+
+            // half-close so the client sees EOF right away, without a reset from
+            // anything it pipelined behind this request
+            if close {
+                self.close_unread(&mut stream, &mut buf_reader);
+                break;
+            }
+        }
+    }
+
+    // run a bodiless request through the routes, returning the raw response
+    fn synthetic_request(&self, method: &str, path: &str, host: Option<&str>) -> Vec<u8> {
+        let mut headers = HashMap::new();
+        if let Some(host) = host {
+            headers.insert("Host".to_string(), host.to_string());
+        }
+        let mut req = Request::new(
+            &format!("{} {} HTTP/1.1", method, path),
+            headers,
+            String::new(),
+        );
+
+        let mut out = Vec::new();
+        let mut res = Response::new(&mut out);
+        res.set_options(&self.response_options);
+        if method == "HEAD" {
+            res.omit_body();
+        }
+        if let Some(cors) = &self.cors {
+            cors.apply(&req, &mut res);
+        }
+        self.dispatch(&mut req, &mut res);
+        out
+    }
+
+    // bind `address`, retrying as configured with `bind_retry`
+    fn bind(&self, address: &str) -> io::Result<TcpListener> {
+        let (attempts, delay) = self.bind_retry;
+        let mut attempt = 1;
+        loop {
+            match TcpListener::bind(address) {
+                Ok(listener) => return Ok(listener),
+                Err(err) if attempt >= attempts => return Err(err),
+                Err(err) => {
+                    eprintln!(
+                        "[rxpress warning!]: failed to bind {} ({}), retrying in {:?}.",
+                        address, err, delay
+                    );
+                    attempt += 1;
+                    thread::sleep(delay);
+                }
+            }
+        }
+    }
+
+    // route a fully read request, running the body transformer afterwards
+    fn dispatch(&self, req: &mut Request, res: &mut Response) {
+        if self.response_options.body_transform.is_some() {
+            res.defer_body();
+        }
+        let router = self.router_for(req);
+        let preflight = self
+            .cors
+            .as_ref()
+            .is_some_and(|cors| cors.preflight(req, res, &router.allowed_methods(&req.path)));
+        if !preflight {
+            router.handle(req, res);
+        }
+        res.flush_deferred(req);
+    }
+
+    // bind the main address and every extra listener
+    fn bind_all(&self) -> io::Result<Vec<TcpListener>> {
+        std::iter::once(&self.address)
+            .chain(&self.extra_addresses)
+            .map(|address| {
+                let listener = self.bind(address)?;
+                println!("[rxpress] running on http://{} ⚙️", address);
+                Ok(listener)
+            })
+            .collect()
+    }
+
+    // run the accept loops and workers until `stop` is set (forever without one)
+    fn serve(&self, listeners: &[TcpListener], stop: Option<&AtomicBool>) {
+        self.shutdown.reset();
+        let pool = ThreadPool::new(self.workers, self.queue_policy);
+        let work = |stream: TcpStream| self.handle_connection(stream);
+
+        thread::scope(|scope| {
+            let pool = (self.workers > 0).then(|| {
+                pool.start(scope, &work);
+                &pool
+            });
+            let loops: Vec<_> = listeners
+                .iter()
+                .map(|listener| scope.spawn(move || self.accept_loop(listener, pool)))
+                .collect();
+
+            if let Some(stop) = stop {
+                scope.spawn(move || {
+                    while !stop.load(Ordering::SeqCst) {
+                        thread::sleep(SHUTDOWN_POLL);
+                    }
+                    self.shutdown.trigger();
+                    // wake every accept loop blocked in `accept()`
+                    for listener in listeners {
+                        if let Ok(mut address) = listener.local_addr() {
+                            if address.ip().is_unspecified() {
+                                address.set_ip(std::net::Ipv4Addr::LOCALHOST.into());
+                            }
+                            let _ = TcpStream::connect(address);
+                        }
+                    }
+                });
+            }
+
+            // workers drain what is queued, then exit
+            for accept_loop in loops {
+                let _ = accept_loop.join();
+            }
+            if let Some(pool) = pool {
+                pool.close();
+            }
+        });
+    }
+
+    // serve connections from one listener, handing them to the pool if there is one
+    fn accept_loop(&self, listener: &TcpListener, pool: Option<&ThreadPool<TcpStream>>) {
+        for stream in listener.incoming() {
+            if self.shutdown.is_ready() {
+                break;
+            }
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    eprintln!("Connection failed: {}", err);
+                    continue;
+                }
+            };
+
+            match pool {
+                Some(pool) => {
+                    // the queue is full under `QueuePolicy::Reject`
+                    if let Err(mut stream) = pool.execute(stream)
+                        && let Ok(read_half) = stream.try_clone()
+                    {
+                        let mut buf_reader = BufReader::new(read_half);
+                        self.reject(&mut stream, &mut buf_reader, HttpStatus::ServiceUnavailable);
+                    }
+                }
+                None => self.handle_connection(stream),
+            }
+        }
+    }
+
+    // find the handler for a protocol the request asks to upgrade to
+    fn upgrade_for(&self, req: &Request) -> Option<(&str, UpgradeHandler)> {
+        let asks_upgrade = req.http_version() == &HttpVersion::Http11
+            && req.header("Connection").is_some_and(|c| {
+                c.split(',')
+                    .any(|token| token.trim().eq_ignore_ascii_case("upgrade"))
+            });
+        if !asks_upgrade {
+            return None;
+        }
+
+        // the client lists protocols in order of preference
+        req.header("Upgrade")?.split(',').find_map(|offered| {
+            self.upgrades
+                .iter()
+                .find(|(protocol, _)| protocol.eq_ignore_ascii_case(offered.trim()))
+                .map(|(protocol, handler)| (protocol.as_str(), *handler))
+        })
+    }
+
+    // confirm the switch and give the connection to the protocol handler
+    fn upgrade(mut stream: TcpStream, protocol: &str, handler: UpgradeHandler, req: Request) {
+        let head = format!(
+            "HTTP/1.1 101 Switching Protocols\r\nConnection: Upgrade\r\nUpgrade: {}\r\n\r\n",
+            protocol
+        );
+        if let Err(err) = stream.write_all(head.as_bytes()) {
+            eprintln!("[rxpress error]: failed to switch protocols: {}", err);
+            return;
+        }
+        let _ = stream.set_read_timeout(None);
+        let _ = stream.set_write_timeout(None);
+        handler(stream, req);
+    }
+
+    // report a finished request to the taps, unless it was a health check
+    fn run_taps(&self, req: &Request, res: &Response) {
+        if self.quiet_paths.contains(&req.path) {
+            return;
+        }
+        self.taps.iter().for_each(|tap| tap(req, res));
+    }
+
+    // pick the virtual host router for the request, or the default one
+    fn router_for(&self, req: &Request) -> &Router {
+        let host = match req.header("Host") {
+            Some(host) => host,
+            None => return &self.router,
+        };
+        // strip the port, keeping bracketed IPv6 literals intact
+        let name = match host.rsplit_once(':') {
+            Some((name, port)) if !port.contains(']') => name,
+            _ => host.as_str(),
+        };
+
+        self.hosts
+            .iter()
+            .find(|(h, _)| h.eq_ignore_ascii_case(name))
+            .map_or(&self.router, |(_, router)| router)
+    }
+
+    // answer a request that couldn't be parsed, then close the connection
+    fn reject<S: Connection>(
+        &self,
+        stream: &mut S,
+        buf_reader: &mut BufReader<S>,
+        status: HttpStatus,
+    ) {
+        let mut res = Response::new(stream);
+        res.set_options(&self.response_options);
+        res.set_header("Connection", "close");
+        if status == HttpStatus::UnsupportedMediaType {
+            // the body encoding was refused; name the one we can read (RFC 7694)
+            res.set_header("Accept-Encoding", "identity");
+        }
+        res.status(status).send(HttpStatus::reason(status.code()));
+
+        self.close_unread(stream, buf_reader);
+    }
+
+    // close a connection whose request wasn't read to the end
+    fn close_unread<S: Connection>(&self, stream: &mut S, buf_reader: &mut BufReader<S>) {
+        // drain what the client already sent so closing doesn't reset the
+        // connection before it reads the response
+        stream.shutdown_write();
+        buf_reader
+            .get_ref()
+            .set_read_timeout(Duration::from_millis(100));
+        let _ = io::copy(&mut buf_reader.take(1024 * 1024), &mut io::sink());
+    }
+}
+
+/// A byte stream the server can serve requests over.
+trait Connection: Read + Write + Sized {
+    fn try_clone(&self) -> io::Result<Self>;
+    fn shutdown_write(&self);
+    fn set_read_timeout(&self, timeout: Duration);
+    fn set_write_timeout(&self, timeout: Duration);
+    /// The TCP socket behind the connection, if it is one
+    fn as_tcp(&self) -> Option<&TcpStream> {
+        None
+    }
+}
+
+impl Connection for TcpStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        TcpStream::try_clone(self)
+    }
+
+    fn shutdown_write(&self) {
+        let _ = self.shutdown(Shutdown::Write);
+    }
+
+    fn set_read_timeout(&self, timeout: Duration) {
+        let _ = TcpStream::set_read_timeout(self, Some(timeout));
+    }
+
+    fn set_write_timeout(&self, timeout: Duration) {
+        let _ = TcpStream::set_write_timeout(self, Some(timeout));
+    }
+
+    fn as_tcp(&self) -> Option<&TcpStream> {
+        Some(self)
+    }
+}
+
+#[cfg(unix)]
+impl Connection for std::os::unix::net::UnixStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        std::os::unix::net::UnixStream::try_clone(self)
+    }
+
+    fn shutdown_write(&self) {
+        let _ = self.shutdown(Shutdown::Write);
+    }
+
+    fn set_read_timeout(&self, timeout: Duration) {
+        let _ = std::os::unix::net::UnixStream::set_read_timeout(self, Some(timeout));
+    }
+
+    fn set_write_timeout(&self, timeout: Duration) {
+        let _ = std::os::unix::net::UnixStream::set_write_timeout(self, Some(timeout));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // helper to push a raw request through `handle_connection` and read the reply
+    fn roundtrip(server: &Server, raw: &str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+
+        client.write_all(raw.as_bytes()).unwrap();
+        client.shutdown(Shutdown::Write).unwrap();
+        server.handle_connection(stream);
+
+        // lossy, since compressed bodies are binary
+        let mut out = Vec::new();
+        client.read_to_end(&mut out).unwrap();
+        String::from_utf8_lossy(&out).to_string()
+    }
 
     /// Unit test: Ensure that the server builds the address correctly.
     #[test]
@@ -330,4 +1815,1066 @@ mod tests {
         let server = Server::new("3000");
         assert_eq!(server.address(), "127.0.0.1:3000");
     }
+
+    // helper to grab a port that is free right now
+    fn free_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    // helper to send a request to a running server, retrying until it is up
+    fn request(port: u16, raw: &str) -> String {
+        for _ in 0..100 {
+            if let Ok(mut client) = TcpStream::connect(("127.0.0.1", port)) {
+                client.write_all(raw.as_bytes()).unwrap();
+                client.shutdown(Shutdown::Write).unwrap();
+                let mut out = String::new();
+                client.read_to_string(&mut out).unwrap();
+                return out;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        panic!("server on port {} never came up", port);
+    }
+
+    // TEST - every listener serves the shared routes
+    #[test]
+    fn test_multiple_listeners() {
+        let (main_port, admin_port) = (free_port(), free_port());
+        let mut server = Server::new(&main_port.to_string());
+        server.add_listener(admin_port);
+        server.get("/", |_req, res| res.send("shared"));
+        thread::spawn(move || server.run());
+
+        for port in [main_port, admin_port] {
+            let out = request(port, "GET / HTTP/1.1\r\n\r\n");
+            assert!(out.ends_with("\r\n\r\nshared"));
+        }
+    }
+
+    // TEST - run_until stops the server and tells a streaming handler to finish
+    #[test]
+    fn test_run_until_signals_handlers() {
+        static STOP: AtomicBool = AtomicBool::new(false);
+        static STREAMING: AtomicBool = AtomicBool::new(false);
+
+        let port = free_port();
+        let mut server = Server::new(&port.to_string());
+        server.get("/events", |req, res| {
+            let shutdown = req.shutdown_signal();
+            let mut events = res.json_stream();
+            while !shutdown.wait_timeout(Duration::from_millis(10)) {
+                events.push(&crate::Json::String("tick".into())).unwrap();
+                STREAMING.store(true, Ordering::SeqCst);
+            }
+        });
+        let running = thread::spawn(move || server.run_until(&STOP));
+        let client = thread::spawn(move || request(port, "GET /events HTTP/1.1\r\n\r\n"));
+
+        while !STREAMING.load(Ordering::SeqCst) {
+            thread::sleep(Duration::from_millis(5));
+        }
+        STOP.store(true, Ordering::SeqCst);
+        running.join().unwrap().unwrap();
+
+        // the stream was terminated, and the keep-alive connection closed anyway
+        let out = client.join().unwrap();
+        assert!(out.contains("Transfer-Encoding: chunked\r\n"));
+        assert!(out.contains("Connection: keep-alive\r\n"));
+        assert!(out.contains("\"tick\""));
+        assert!(out.ends_with("]\r\n0\r\n\r\n"));
+    }
+
+    // TEST - a shutdown message stops accepting but lets in-flight requests finish
+    #[test]
+    fn test_run_with_shutdown() {
+        static IN_FLIGHT: AtomicBool = AtomicBool::new(false);
+
+        let port = free_port();
+        let (stop, shutdown) = std::sync::mpsc::channel();
+        let mut server = Server::new(&port.to_string());
+        server.get("/slow", |_req, res| {
+            IN_FLIGHT.store(true, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(100));
+            res.send("done");
+        });
+        let running = thread::spawn(move || server.run_with_shutdown(shutdown));
+        let client = thread::spawn(move || request(port, "GET /slow HTTP/1.1\r\n\r\n"));
+
+        while !IN_FLIGHT.load(Ordering::SeqCst) {
+            thread::sleep(Duration::from_millis(5));
+        }
+        stop.send(()).unwrap();
+        running.join().unwrap().unwrap();
+
+        assert!(client.join().unwrap().ends_with("\r\n\r\ndone"));
+        assert!(TcpStream::connect(("127.0.0.1", port)).is_err());
+
+        // the port is free, so the app can be restarted on it
+        let mut server = Server::new(&port.to_string());
+        server.get("/", |_req, res| res.send("again"));
+        let (stop, shutdown) = std::sync::mpsc::channel::<()>();
+        let running = thread::spawn(move || server.run_with_shutdown(shutdown));
+        assert!(request(port, "GET / HTTP/1.1\r\n\r\n").ends_with("again"));
+        drop(stop);
+        running.join().unwrap().unwrap();
+    }
+
+    // TEST - an upgrade request gets 101 and the handler owns the raw stream
+    #[test]
+    fn test_on_upgrade() {
+        use std::io::BufRead;
+
+        let mut server = Server::new("0");
+        server.on_upgrade("echo/1", |mut stream, req| {
+            let mut line = String::new();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            reader.read_line(&mut line).unwrap();
+            write!(stream, "{} {}", req.path, line.to_uppercase()).unwrap();
+        });
+        server.get("/chat", |_req, res| res.send("plain"));
+
+        // no upgrade asked for: the route answers
+        let out = roundtrip(&server, "GET /chat HTTP/1.1\r\nUpgrade: echo/1\r\n\r\n");
+        assert!(out.ends_with("\r\n\r\nplain"));
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        thread::scope(|scope| {
+            scope.spawn(|| server.handle_connection(stream));
+
+            client
+                .write_all(
+                    b"GET /chat HTTP/1.1\r\nConnection: keep-alive, Upgrade\r\nUpgrade: h2c, ECHO/1\r\n\r\n",
+                )
+                .unwrap();
+            let mut reader = BufReader::new(client.try_clone().unwrap());
+            let mut head = String::new();
+            while !head.ends_with("\r\n\r\n") {
+                reader.read_line(&mut head).unwrap();
+            }
+            assert_eq!(
+                head,
+                "HTTP/1.1 101 Switching Protocols\r\nConnection: Upgrade\r\nUpgrade: echo/1\r\n\r\n"
+            );
+
+            client.write_all(b"ping\n").unwrap();
+            let mut rest = String::new();
+            reader.read_to_string(&mut rest).unwrap();
+            assert_eq!(rest, "/chat PING\n");
+        });
+    }
+
+    // TEST - requests know which listener served them, and the client's address
+    #[test]
+    fn test_connection_addrs() {
+        let (main_port, admin_port) = (free_port(), free_port());
+        let mut server = Server::new(&main_port.to_string());
+        server.add_listener(admin_port);
+        server.get("/", |req, res| {
+            let (local, peer) = (req.local_addr().unwrap(), req.peer_addr().unwrap());
+            res.send(&format!("{} {}", local, peer.ip()));
+        });
+        thread::spawn(move || server.run());
+
+        for port in [main_port, admin_port] {
+            let out = request(port, "GET / HTTP/1.1\r\n\r\n");
+            assert!(out.ends_with(&format!("\r\n\r\n127.0.0.1:{} 127.0.0.1", port)));
+        }
+    }
+
+    // TEST - requests are served over a unix domain socket
+    #[cfg(unix)]
+    #[test]
+    fn test_run_unix() {
+        use std::os::unix::net::UnixStream;
+
+        let path = std::env::temp_dir().join(format!("rxpress-{}.sock", std::process::id()));
+        let path = path.to_str().unwrap().to_string();
+        let mut server = Server::new("0");
+        server.get("/", |_req, res| res.send("ipc"));
+
+        let err = server
+            .run_unix("/nonexistent-dir/rxpress.sock")
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        // a stale socket from an earlier run must not block binding
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        let socket = path.clone();
+        thread::spawn(move || server.run_unix(&socket));
+
+        for _ in 0..100 {
+            if let Ok(mut client) = UnixStream::connect(&path) {
+                client.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+                client.shutdown(Shutdown::Write).unwrap();
+                let mut out = String::new();
+                client.read_to_string(&mut out).unwrap();
+                assert!(out.starts_with("HTTP/1.1 200 OK\r\n"));
+                assert!(out.ends_with("\r\n\r\nipc"));
+                let _ = std::fs::remove_file(&path);
+                return;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        panic!("unix socket server never came up");
+    }
+
+    // helper to overload a single worker: one slow request in flight, the
+    // rest arriving while it runs; returns each client's status line
+    fn saturate(policy: QueuePolicy, clients: u64) -> Vec<String> {
+        let port = free_port();
+        let mut server = Server::new(&port.to_string());
+        server.workers(1);
+        server.queue_policy(policy);
+        server.get("/slow", |_req, res| {
+            thread::sleep(Duration::from_millis(100));
+            res.send("done");
+        });
+        thread::spawn(move || server.run());
+        request(port, "GET /warmup HTTP/1.1\r\n\r\n");
+
+        let handles: Vec<_> = (0..clients)
+            .map(|i| {
+                thread::spawn(move || {
+                    thread::sleep(Duration::from_millis(20 * i));
+                    let out = request(port, "GET /slow HTTP/1.1\r\n\r\n");
+                    out.lines().next().unwrap_or_default().to_string()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    }
+
+    // TEST - a full queue under `Reject` answers 503
+    #[test]
+    fn test_queue_policy_reject() {
+        let lines = saturate(QueuePolicy::Reject(1), 4);
+        // one request in flight, one queued, the rest turned away
+        assert_eq!(lines[..2], ["HTTP/1.1 200 OK", "HTTP/1.1 200 OK"]);
+        assert!(
+            lines[2..]
+                .iter()
+                .all(|l| l == "HTTP/1.1 503 Service Unavailable")
+        );
+    }
+
+    // TEST - `Block` and `Unbounded` make late clients wait instead
+    #[test]
+    fn test_queue_policy_block_and_unbounded() {
+        let start = std::time::Instant::now();
+        let lines = saturate(QueuePolicy::Block(1), 4);
+        assert!(lines.iter().all(|l| l == "HTTP/1.1 200 OK"));
+        // a single worker serves the four slow requests one after another
+        assert!(start.elapsed() >= Duration::from_millis(400));
+
+        let lines = saturate(QueuePolicy::Unbounded, 6);
+        assert!(lines.iter().all(|l| l == "HTTP/1.1 200 OK"));
+    }
+
+    // TEST - HTTP/1.0 clients get an HTTP/1.0 status line and `Connection: close`
+    #[test]
+    fn test_http10_response() {
+        let mut server = Server::new("0");
+        server.get("/", |_req, res| res.send("legacy"));
+
+        let out = roundtrip(&server, "GET / HTTP/1.0\r\n\r\n");
+        assert!(out.starts_with("HTTP/1.0 200 OK\r\n"));
+        assert!(out.contains("Connection: close\r\n"));
+
+        let out = roundtrip(&server, "GET / HTTP/1.1\r\n\r\n");
+        assert!(out.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(!out.contains("Connection: close"));
+    }
+
+    // TEST - reason phrase overrides show up in the status line
+    #[test]
+    fn test_status_reason_override() {
+        let mut server = Server::new("0");
+        server.status_reasons(HashMap::from([(404, "Nope".to_string())]));
+        server.get("/", |_req, res| res.send("ok"));
+
+        let out = roundtrip(&server, "GET /missing HTTP/1.1\r\n\r\n");
+        assert!(out.starts_with("HTTP/1.1 404 Nope\r\n"));
+
+        let out = roundtrip(&server, "GET / HTTP/1.1\r\n\r\n");
+        assert!(out.starts_with("HTTP/1.1 200 OK\r\n"));
+    }
+
+    // TEST - status-class hooks only touch matching responses
+    #[test]
+    fn test_status_class_hook() {
+        let mut server = Server::new("0");
+        server.on_status_class(StatusClass::ServerError, |res| {
+            res.set_header("X-Error", "true");
+        });
+        server.get("/ok", |_req, res| res.send("fine"));
+        server.get("/boom", |_req, res| res.status(500).send("broken"));
+
+        let out = roundtrip(&server, "GET /boom HTTP/1.1\r\n\r\n");
+        assert!(out.starts_with("HTTP/1.1 500"));
+        assert!(out.contains("X-Error: true\r\n"));
+
+        let out = roundtrip(&server, "GET /ok HTTP/1.1\r\n\r\n");
+        assert!(!out.contains("X-Error"));
+    }
+
+    // TEST - the default language applies unless the handler overrides it
+    #[test]
+    fn test_default_language() {
+        let mut server = Server::new("0");
+        server.default_language("en-US");
+        server.get("/", |_req, res| res.send("hello"));
+        server.get("/de", |_req, res| {
+            res.content_language("de-DE").send("hallo");
+        });
+
+        let out = roundtrip(&server, "GET / HTTP/1.1\r\n\r\n");
+        assert!(out.contains("Content-Language: en-US\r\n"));
+
+        let out = roundtrip(&server, "GET /de HTTP/1.1\r\n\r\n");
+        assert!(out.contains("Content-Language: de-DE\r\n"));
+        assert!(!out.contains("en-US"));
+    }
+
+    // TEST - embedded assets are served with their MIME type
+    #[test]
+    fn test_static_embedded() {
+        static ASSETS: EmbeddedAssets = &[("app.js", b"console.log(1);")];
+        let mut server = Server::new("0");
+        server.static_embedded("/static", ASSETS);
+
+        let out = roundtrip(&server, "GET /static/app.js HTTP/1.1\r\n\r\n");
+        assert!(out.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(out.contains("Content-Type: text/javascript; charset=utf-8\r\n"));
+        assert!(out.ends_with("\r\n\r\nconsole.log(1);"));
+
+        let out = roundtrip(&server, "GET /static/missing.css HTTP/1.1\r\n\r\n");
+        assert!(out.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+
+    // TEST - the body transformer rewrites bodies and their length
+    #[test]
+    fn test_transform_body() {
+        let mut server = Server::new("0");
+        server.transform_body(|_req, body, content_type| {
+            if content_type.starts_with("text/plain") {
+                let mut out = body.to_ascii_uppercase();
+                out.extend_from_slice(b"!!");
+                out
+            } else {
+                body.to_vec()
+            }
+        });
+        server.get("/text", |_req, res| res.send("hello world"));
+        server.get("/json", |_req, res| res.json(r#"{"a":"b"}"#));
+
+        let out = roundtrip(&server, "GET /text HTTP/1.1\r\n\r\n");
+        assert!(out.contains("Content-Length: 13\r\n"));
+        assert!(out.ends_with("\r\n\r\nHELLO WORLD!!"));
+
+        let out = roundtrip(&server, "GET /json HTTP/1.1\r\n\r\n");
+        assert!(out.ends_with(r#"{"a":"b"}"#));
+    }
+
+    // TEST - stats count requests and body bytes
+    #[test]
+    fn test_stats() {
+        let mut server = Server::new("0");
+        server.get("/", |_req, res| res.send("hello"));
+
+        for _ in 0..3 {
+            roundtrip(&server, "GET / HTTP/1.1\r\n\r\n");
+        }
+        let stats = server.stats();
+        assert_eq!(stats.requests, 3);
+        assert_eq!(stats.bytes_out, 15);
+        assert_eq!(stats.active_connections, 0);
+    }
+
+    // TEST - proxies' absolute-form targets reach the right route
+    #[test]
+    fn test_absolute_form_routing() {
+        let mut server = Server::new("0");
+        server.get("/users/:id", |req, res| {
+            res.send(&format!(
+                "{} {}",
+                req.param_or("id", ""),
+                req.query_or("x", "")
+            ));
+        });
+
+        let out = roundtrip(
+            &server,
+            "GET http://example.com/users/5?x=1 HTTP/1.1\r\n\r\n",
+        );
+        assert!(out.starts_with("HTTP/1.1 200 OK"));
+        assert!(out.ends_with("\r\n\r\n5 1"));
+    }
+
+    // TEST - a pre-body hook rejects an upload without reading it
+    #[test]
+    fn test_before_body_rejects_upload() {
+        let mut server = Server::new("0");
+        server.before_body(|req, res| {
+            if req.header("Authorization").is_none() {
+                res.status(401).send("Unauthorized");
+                return false;
+            }
+            true
+        });
+        server.post("/upload", |req, res| {
+            res.send(&format!("got {}", req.body.len()));
+        });
+
+        // announces 1 GiB but only the headers ever arrive
+        let raw = "POST /upload HTTP/1.1\r\nContent-Length: 1073741824\r\n\r\npartial";
+        let out = roundtrip(&server, raw);
+        assert!(out.starts_with("HTTP/1.1 401 Unauthorized\r\n"));
+        assert!(out.ends_with("\r\n\r\nUnauthorized"));
+        assert_eq!(server.stats().requests, 0);
+
+        let raw = "POST /upload HTTP/1.1\r\nAuthorization: yes\r\nContent-Length: 3\r\n\r\nabc";
+        let out = roundtrip(&server, raw);
+        assert!(out.ends_with("\r\n\r\ngot 3"));
+    }
+
+    // TEST - CORS preflights reflect each path's registered methods
+    #[test]
+    fn test_cors_preflight_per_route() {
+        let mut server = Server::new("0");
+        server.cors(Cors::new(crate::cors::AllowOrigin::Any));
+        server.get("/a", |_req, res| res.send("a"));
+        server.get("/b", |_req, res| res.send("b"));
+        server.post("/b", |_req, res| res.send("b"));
+
+        let preflight = |path: &str| {
+            let raw = format!(
+                "OPTIONS {} HTTP/1.1\r\nOrigin: https://x.test\r\nAccess-Control-Request-Method: POST\r\n\r\n",
+                path
+            );
+            roundtrip(&server, &raw)
+        };
+
+        let out = preflight("/a");
+        assert!(out.starts_with("HTTP/1.1 204 No Content\r\n"));
+        assert!(out.contains("Access-Control-Allow-Methods: GET\r\n"));
+
+        let out = preflight("/b");
+        assert!(out.contains("Access-Control-Allow-Methods: GET, POST\r\n"));
+
+        // unknown paths are not preflighted
+        assert!(preflight("/missing").starts_with("HTTP/1.1 404"));
+    }
+
+    // TEST - `?` in a fallible handler turns errors into a 500
+    #[test]
+    fn test_try_route_question_mark() {
+        let mut server = Server::new("0");
+        server.debug_errors(true);
+        server.try_route("GET", "/parse/:n", |req, res| {
+            let n: u32 = req.param_or("n", "").parse()?;
+            res.send(&format!("n={}", n));
+            Ok(())
+        });
+
+        let out = roundtrip(&server, "GET /parse/42 HTTP/1.1\r\n\r\n");
+        assert!(out.starts_with("HTTP/1.1 200 OK"));
+        assert!(out.ends_with("n=42"));
+
+        let out = roundtrip(&server, "GET /parse/abc HTTP/1.1\r\n\r\n");
+        assert!(out.starts_with("HTTP/1.1 500 Internal Server Error"));
+        assert!(out.ends_with("invalid digit found in string"));
+
+        server.debug_errors(false);
+        let out = roundtrip(&server, "GET /parse/abc HTTP/1.1\r\n\r\n");
+        assert!(out.ends_with("\r\n\r\nInternal Server Error"));
+    }
+
+    // TEST - custom limits answer 413, 431, and 414
+    #[test]
+    fn test_request_limits() {
+        let mut server = Server::new("0");
+        server.limits(RequestLimits {
+            max_uri_length: 16,
+            max_header_count: 1,
+            max_body_bytes: 4,
+            ..RequestLimits::default()
+        });
+        server.post("/", |_req, res| res.send("ok"));
+
+        let out = roundtrip(&server, "POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello");
+        assert!(out.starts_with("HTTP/1.1 413 Payload Too Large"));
+
+        let out = roundtrip(&server, "POST / HTTP/1.1\r\nA: 1\r\nB: 2\r\n\r\n");
+        assert!(out.starts_with("HTTP/1.1 431 Request Header Fields Too Large"));
+
+        let out = roundtrip(&server, "POST /a/very/long/target HTTP/1.1\r\n\r\n");
+        assert!(out.starts_with("HTTP/1.1 414 URI Too Long"));
+
+        let out = roundtrip(&server, "POST / HTTP/1.1\r\nContent-Length: 4\r\n\r\nfour");
+        assert!(out.starts_with("HTTP/1.1 200 OK"));
+    }
+
+    // TEST - oversized bodies get 413 up front, without buffering them
+    #[test]
+    fn test_max_body_size() {
+        let mut server = Server::new("0");
+        server.post("/", |_req, res| res.send("ok"));
+
+        // the declared size alone triggers the limit; no body is ever sent
+        let out = roundtrip(
+            &server,
+            "POST / HTTP/1.1\r\nContent-Length: 999999999\r\n\r\n",
+        );
+        assert!(out.starts_with("HTTP/1.1 413 Payload Too Large"));
+
+        server.max_body_size(4);
+        let out = roundtrip(&server, "POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello");
+        assert!(out.starts_with("HTTP/1.1 413 Payload Too Large"));
+        let out = roundtrip(
+            &server,
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n3\r\ndef\r\n0\r\n\r\n",
+        );
+        assert!(out.starts_with("HTTP/1.1 413 Payload Too Large"));
+        let out = roundtrip(&server, "POST / HTTP/1.1\r\nContent-Length: 4\r\n\r\nfour");
+        assert!(out.starts_with("HTTP/1.1 200 OK"));
+    }
+
+    // TEST - a stream to a client that stops reading aborts once a write times out
+    #[test]
+    fn test_write_timeout_aborts_stream() {
+        static ABORTED: AtomicBool = AtomicBool::new(false);
+
+        let mut server = Server::new("0");
+        server.write_timeout(Duration::from_millis(200));
+        server.get("/big", |_req, res| {
+            let len = 1 << 30; // far more than the socket buffers hold
+            let result = res.send_reader(io::repeat(b'x').take(len), len, "text/plain");
+            ABORTED.store(result.is_err(), Ordering::SeqCst);
+        });
+
+        // the client reads nothing until the handler has returned
+        let started = Instant::now();
+        let out = roundtrip(
+            &server,
+            "GET /big HTTP/1.1\r\n\r\nGET /big HTTP/1.1\r\n\r\n",
+        );
+        assert!(ABORTED.load(Ordering::SeqCst));
+        assert!(started.elapsed() < Duration::from_secs(10));
+        // the cut-off response is the last thing on the connection
+        assert_eq!(out.matches("HTTP/1.1 200 OK").count(), 1);
+        assert!(out.len() < 1 << 30);
+    }
+
+    // TEST - with Truncate the handler gets the first bytes and the flag, and the
+    // connection stays usable for the next request
+    #[test]
+    fn test_body_truncated() {
+        let mut server = Server::new("0");
+        server.max_body_size(4);
+        server.body_overflow(BodyOverflow::Truncate);
+        server.post("/", |req, res| {
+            res.send(&format!("{} {}", req.body_truncated(), req.body));
+        });
+
+        let out = roundtrip(
+            &server,
+            "POST / HTTP/1.1\r\nContent-Length: 11\r\n\r\nhello worldPOST / HTTP/1.1\r\nContent-Length: 2\r\n\r\nok",
+        );
+        assert!(out.starts_with("HTTP/1.1 200 OK"));
+        assert!(out.contains("\r\n\r\ntrue hell"));
+        assert!(out.ends_with("\r\n\r\nfalse ok"));
+    }
+
+    // TEST - a chunked body is cut off with 413 once it outgrows the total cap
+    #[test]
+    fn test_chunked_body_total_limit() {
+        let mut server = Server::new("0");
+        server.limits(RequestLimits {
+            max_total_body: 64,
+            ..RequestLimits::default()
+        });
+        server.post("/", |req, res| res.send(&req.body.len().to_string()));
+
+        let chunk = format!("10\r\n{}\r\n", "x".repeat(16));
+        let raw = format!(
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n{}",
+            chunk.repeat(10)
+        );
+        let out = roundtrip(&server, &raw);
+        assert!(out.starts_with("HTTP/1.1 413 Payload Too Large"));
+
+        let raw = format!(
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n{}0\r\n\r\n",
+            chunk.repeat(4)
+        );
+        let out = roundtrip(&server, &raw);
+        assert!(out.starts_with("HTTP/1.1 200 OK") && out.ends_with("\r\n\r\n64"));
+    }
+
+    // TEST - request bodies in an encoding we can't decode get 415
+    #[test]
+    fn test_unsupported_content_encoding() {
+        let mut server = Server::new("0");
+        server.post("/", |req, res| res.send(&req.body));
+
+        let out = roundtrip(
+            &server,
+            "POST / HTTP/1.1\r\nContent-Encoding: br\r\nContent-Length: 5\r\n\r\n\x1b\x04\x00\x78\x03",
+        );
+        assert!(out.starts_with("HTTP/1.1 415 Unsupported Media Type"));
+        assert!(out.contains("Accept-Encoding: identity\r\n"));
+
+        let out = roundtrip(
+            &server,
+            "POST / HTTP/1.1\r\nContent-Encoding: identity\r\nContent-Length: 5\r\n\r\nplain",
+        );
+        assert!(out.starts_with("HTTP/1.1 200 OK") && out.ends_with("\r\n\r\nplain"));
+    }
+
+    // TEST - a `Connection: close` response ends with a clean EOF
+    #[test]
+    fn test_close_response_half_closes() {
+        let mut server = Server::new("0");
+        server.get("/", |_req, res| res.send("bye"));
+        server.get("/drop", |_req, res| {
+            res.set_header("Connection", "close").send("dropped");
+        });
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        for (raw, body) in [
+            // the pipelined request behind the close is never answered
+            (
+                "GET / HTTP/1.1\r\nConnection: close\r\n\r\nGET / HTTP/1.1\r\n\r\n",
+                "bye",
+            ),
+            (
+                "GET /drop HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\n\r\n",
+                "dropped",
+            ),
+        ] {
+            let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+            client
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            let (stream, _) = listener.accept().unwrap();
+
+            // the client keeps its write half open, so EOF has to come from the server
+            client.write_all(raw.as_bytes()).unwrap();
+            thread::scope(|scope| {
+                scope.spawn(|| server.handle_connection(stream));
+
+                let mut out = String::new();
+                client.read_to_string(&mut out).unwrap();
+                assert_eq!(out.matches("HTTP/1.1 200 OK").count(), 1);
+                assert!(out.ends_with(body));
+            });
+        }
+    }
+
+    // TEST - slow requests on a pool are served side by side
+    #[test]
+    fn test_workers_serve_in_parallel() {
+        let mut server = Server::new("0");
+        server.workers(4);
+        server.get("/slow", |_req, res| {
+            thread::sleep(Duration::from_millis(300));
+            res.send("done");
+        });
+        let client = crate::test_util::TestClient::new(server);
+
+        let start = std::time::Instant::now();
+        thread::scope(|scope| {
+            let calls: Vec<_> = (0..4)
+                .map(|_| scope.spawn(|| client.get("/slow")))
+                .collect();
+            for call in calls {
+                assert_eq!(call.join().unwrap().body, "done");
+            }
+        });
+        // one after another this would take at least 1.2s
+        assert!(start.elapsed() < Duration::from_millis(900));
+    }
+
+    // TEST - a panicking handler doesn't take its worker down with it
+    #[test]
+    fn test_worker_survives_panic() {
+        let mut server = Server::new("0");
+        server.workers(1);
+        server.get("/boom", |_req, _res| panic!("handler bug"));
+        server.get("/ok", |_req, res| res.send("still here"));
+        let client = crate::test_util::TestClient::new(server);
+
+        for _ in 0..2 {
+            let mut stream = TcpStream::connect(client.address()).unwrap();
+            stream.write_all(b"GET /boom HTTP/1.1\r\n\r\n").unwrap();
+            let mut out = Vec::new();
+            stream.read_to_end(&mut out).unwrap();
+            assert!(out.is_empty());
+        }
+        assert_eq!(client.get("/ok").body, "still here");
+    }
+
+    // TEST - taps see matched routes and 404s alike
+    #[test]
+    fn test_tap() {
+        static SEEN: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+        let mut server = Server::new("0");
+        server.get("/hello", |_req, res| res.send("hi"));
+        server.tap(|req, res| {
+            let line = format!("{} {}", req.path, res.status_code());
+            SEEN.lock().unwrap().push(line);
+        });
+
+        roundtrip(&server, "GET /hello HTTP/1.1\r\n\r\n");
+        roundtrip(&server, "GET /missing HTTP/1.1\r\n\r\n");
+        assert_eq!(*SEEN.lock().unwrap(), vec!["/hello 200", "/missing 404"]);
+    }
+
+    // TEST - large JSON bodies are gzipped only for clients that accept it
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_enable_compression() {
+        let mut server = Server::new("0");
+        server.enable_compression(64);
+        server.get("/big", |_req, res| {
+            res.json(&format!("[{}1]", "1,".repeat(100)))
+        });
+        server.get("/small", |_req, res| res.json("[1]"));
+
+        let body = format!("[{}1]", "1,".repeat(100));
+        let packed = crate::gzip::compress(body.as_bytes());
+        let out = roundtrip(
+            &server,
+            "GET /big HTTP/1.1\r\nAccept-Encoding: gzip, br\r\nConnection: close\r\n\r\n",
+        );
+        assert!(out.contains("Content-Encoding: gzip\r\n"));
+        assert!(out.contains("Vary: Accept-Encoding\r\n"));
+        assert!(out.contains(&format!("Content-Length: {}\r\n", packed.len())));
+
+        let out = roundtrip(&server, "GET /big HTTP/1.1\r\nConnection: close\r\n\r\n");
+        assert!(!out.contains("Content-Encoding") && out.ends_with(&body));
+        assert!(out.contains("Vary: Accept-Encoding\r\n"));
+
+        let out = roundtrip(
+            &server,
+            "GET /small HTTP/1.1\r\nAccept-Encoding: gzip\r\nConnection: close\r\n\r\n",
+        );
+        assert!(!out.contains("Content-Encoding") && out.ends_with("\r\n\r\n[1]"));
+    }
+
+    // TEST - the health check answers `ok` and stays out of the taps
+    #[test]
+    fn test_health_check() {
+        static SEEN: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+        let mut server = Server::new("0");
+        server.health_check("/healthz");
+        server.health_check_with("/readyz", |_req, res| res.status(503).send("warming up"));
+        server.get("/hello", |_req, res| res.send("hi"));
+        server.tap(|req, _res| SEEN.lock().unwrap().push(req.path.clone()));
+
+        let out = roundtrip(&server, "GET /healthz HTTP/1.1\r\n\r\n");
+        assert!(out.starts_with("HTTP/1.1 200 OK") && out.ends_with("\r\n\r\nok"));
+        let out = roundtrip(&server, "GET /readyz HTTP/1.1\r\n\r\n");
+        assert!(out.starts_with("HTTP/1.1 503") && out.ends_with("\r\n\r\nwarming up"));
+        roundtrip(&server, "GET /hello HTTP/1.1\r\n\r\n");
+
+        assert_eq!(*SEEN.lock().unwrap(), vec!["/hello"]);
+    }
+
+    // TEST - middleware reads the body to sign it, the handler still parses it
+    #[test]
+    fn test_body_read_twice() {
+        let mut server = Server::new("0");
+        server.use_middleware(|req, res| {
+            let mut body = Vec::new();
+            if let Err(err) = req.buffer_body() {
+                res.status(err.status()).send(err.message());
+                return false;
+            }
+            req.body_reader().read_to_end(&mut body).unwrap();
+            let signature = body
+                .iter()
+                .fold(0u32, |acc, b| acc.wrapping_mul(31) ^ *b as u32);
+            res.set_header("X-Signature", &signature.to_string());
+            true
+        });
+        server.post("/orders", |req, res| match req.json() {
+            Ok(order) => res.send(&order.to_string()),
+            Err(err) => res.status(400).send(&err),
+        });
+
+        let body = r#"{"id": 42}"#;
+        let out = roundtrip(
+            &server,
+            &format!(
+                "POST /orders HTTP/1.1\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            ),
+        );
+        let signature = body
+            .bytes()
+            .fold(0u32, |acc, b| acc.wrapping_mul(31) ^ b as u32);
+        assert!(out.contains(&format!("X-Signature: {}\r\n", signature)));
+        assert!(out.ends_with("\r\n\r\n{\"id\":42}"));
+    }
+
+    // TEST - one connection carries several requests until the cap or idle timeout
+    #[test]
+    fn test_keep_alive() {
+        use std::io::BufRead;
+
+        // read one response (head and Content-Length body) off a live connection
+        fn read_response(reader: &mut BufReader<TcpStream>) -> String {
+            let mut out = String::new();
+            let mut len = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.strip_prefix("Content-Length: ") {
+                    len = value.trim().parse().unwrap();
+                }
+                out.push_str(&line);
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; len];
+            reader.read_exact(&mut body).unwrap();
+            out + &String::from_utf8(body).unwrap()
+        }
+
+        let mut server = Server::new("0");
+        server.max_requests_per_connection(2);
+        server.keep_alive_timeout(Duration::from_millis(200));
+        server.get("/", |req, res| res.send(&req.path));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                for _ in 0..2 {
+                    server.handle_connection(listener.accept().unwrap().0);
+                }
+            });
+
+            // two requests in turn, the second one hits the cap
+            let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+            client
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            let mut reader = BufReader::new(client.try_clone().unwrap());
+            client.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+            assert!(read_response(&mut reader).contains("Connection: keep-alive\r\n"));
+            thread::sleep(Duration::from_millis(50));
+            client.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+            assert!(read_response(&mut reader).contains("Connection: close\r\n"));
+            assert_eq!(reader.read(&mut [0; 1]).unwrap(), 0);
+
+            // an idle connection is closed once the timeout runs out
+            let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+            client
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            let mut reader = BufReader::new(client.try_clone().unwrap());
+            (&client)
+                .write_all(b"GET / HTTP/1.0\r\nConnection: keep-alive\r\n\r\n")
+                .unwrap();
+            assert!(read_response(&mut reader).starts_with("HTTP/1.0 200 OK"));
+            let start = std::time::Instant::now();
+            assert_eq!(reader.read(&mut [0; 1]).unwrap(), 0);
+            assert!(start.elapsed() < Duration::from_secs(2));
+        });
+    }
+
+    // TEST - ambiguous body framing is refused, so nothing rides along on keep-alive
+    #[test]
+    fn test_keep_alive_rejects_ambiguous_length() {
+        let mut server = Server::new("0");
+        server.get("/", |_req, res| res.send("home"));
+        server.post("/", |req, res| res.send(&req.body));
+        server.get("/secret", |_req, res| res.send("SECRET"));
+
+        let smuggle = |framing: &str| {
+            let raw = format!(
+                "POST / HTTP/1.1\r\n{}\r\n\r\nGET /secret HTTP/1.1\r\n\r\n",
+                framing
+            );
+            roundtrip(&server, &raw)
+        };
+        for framing in [
+            "Content-Length: abc",
+            "Content-Length: +0",
+            "Content-Length: 0\r\nContent-Length: 30",
+            "Content-Length: 30\r\nTransfer-Encoding: chunked",
+        ] {
+            let out = smuggle(framing);
+            assert!(out.starts_with("HTTP/1.1 400 Bad Request"), "{}", framing);
+            assert_eq!(out.matches("HTTP/1.1 ").count(), 1, "{}", framing);
+            assert!(!out.contains("SECRET"), "{}", framing);
+        }
+
+        // the same length repeated is unambiguous and the connection stays usable
+        let out = roundtrip(
+            &server,
+            "POST / HTTP/1.1\r\nContent-Length: 2\r\nContent-Length: 2\r\n\r\nhi\
+             GET /secret HTTP/1.1\r\n\r\n",
+        );
+        assert_eq!(out.matches("HTTP/1.1 200 OK").count(), 2);
+        assert!(out.contains("\r\n\r\nhi") && out.ends_with("SECRET"));
+    }
+
+    // TEST - binding is retried until the port frees up
+    #[test]
+    fn test_bind_retry() {
+        let holder = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = holder.local_addr().unwrap().to_string();
+        let mut server = Server::new("0");
+
+        // a single attempt fails while the port is taken
+        assert!(server.bind(&address).is_err());
+
+        // the port is freed while the server waits between attempts
+        server.bind_retry(5, Duration::from_millis(100));
+        let release = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            drop(holder);
+        });
+        assert!(server.bind(&address).is_ok());
+        release.join().unwrap();
+    }
+
+    // TEST - run hands back the bind error instead of panicking
+    #[test]
+    fn test_run_returns_bind_error() {
+        let holder = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = holder.local_addr().unwrap().port();
+        let server = Server::new(&port.to_string());
+
+        let err = server.run().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+    }
+
+    // TEST - an oversized header line is rejected with 431
+    #[test]
+    fn test_header_line_too_long() {
+        let mut server = Server::new("0");
+        server.max_header_line_bytes(1024);
+        server.get("/", |_req, res| res.send("ok"));
+
+        let raw = format!("GET / HTTP/1.1\r\nX-Big: {}\r\n\r\n", "a".repeat(64 * 1024));
+        let out = roundtrip(&server, &raw);
+        assert!(out.starts_with("HTTP/1.1 431 Request Header Fields Too Large"));
+
+        // a header right at the limit is fine
+        let raw = format!("GET / HTTP/1.1\r\nX-Big: {}\r\n\r\n", "a".repeat(1024 - 7));
+        let out = roundtrip(&server, &raw);
+        assert!(out.starts_with("HTTP/1.1 200 OK"));
+    }
+
+    // TEST - the Host header selects the router
+    #[test]
+    fn test_host_route() {
+        let mut api = Router::new();
+        api.add_route("GET", "/", |_req, res| res.send("api"));
+        let mut www = Router::new();
+        www.add_route("GET", "/", |_req, res| res.send("www"));
+
+        let mut server = Server::new("0");
+        server.host_route("api.example.com", api);
+        server.host_route("www.example.com", www);
+        server.get("/", |_req, res| res.send("default"));
+
+        let out = roundtrip(&server, "GET / HTTP/1.1\r\nHost: api.example.com\r\n\r\n");
+        assert!(out.ends_with("\r\n\r\napi"));
+        let out = roundtrip(
+            &server,
+            "GET / HTTP/1.1\r\nHost: WWW.example.com:8080\r\n\r\n",
+        );
+        assert!(out.ends_with("\r\n\r\nwww"));
+        let out = roundtrip(&server, "GET / HTTP/1.1\r\nHost: other.example.com\r\n\r\n");
+        assert!(out.ends_with("\r\n\r\ndefault"));
+    }
+
+    // TEST - two pipelined requests sent in one write are both answered
+    #[test]
+    fn test_pipelined_requests() {
+        let mut server = Server::new("0");
+        server.get("/a", |_req, res| res.send("first"));
+        server.get("/b", |req, res| res.send(&format!("second:{}", req.body)));
+
+        let out = roundtrip(
+            &server,
+            "GET /a HTTP/1.1\r\nHost: x\r\n\r\nGET /b HTTP/1.1\r\nContent-Length: 4\r\n\r\nbody",
+        );
+        assert_eq!(out.matches("HTTP/1.1 200 OK").count(), 2);
+        let first = out.find("first").unwrap();
+        let second = out.find("second:body").unwrap();
+        assert!(first < second);
+
+        // empty bodies don't throw off the framing of the next response
+        server.get("/empty", |_req, res| res.send(""));
+        let out = roundtrip(
+            &server,
+            "GET /empty HTTP/1.1\r\n\r\nGET /a HTTP/1.1\r\n\r\n",
+        );
+        let (empty, rest) = out.split_once("Content-Length: 0\r\n\r\n").unwrap();
+        assert!(empty.starts_with("HTTP/1.1 200 OK"));
+        assert!(rest.starts_with("HTTP/1.1 200 OK") && rest.ends_with("\r\n\r\nfirst"));
+    }
+
+    // TEST - HEAD is answered by the GET route, without the body
+    #[test]
+    fn test_head_falls_back_to_get() {
+        let mut server = Server::new("0");
+        server.get("/hello", |_req, res| {
+            res.send("Hello!");
+        });
+
+        let out = roundtrip(&server, "HEAD /hello HTTP/1.1\r\nConnection: close\r\n\r\n");
+        assert!(out.starts_with("HTTP/1.1 200 OK"));
+        assert!(out.contains("Content-Length: 6\r\n"));
+        assert!(out.ends_with("\r\n\r\n"));
+        assert_eq!(server.stats().bytes_out, 0);
+    }
+
+    // TEST - GET routes pass the HEAD/GET consistency check
+    #[test]
+    fn test_assert_head_get_consistency() {
+        let mut server = Server::new("0");
+        server.get("/", |_req, res| {
+            res.send("home");
+        });
+        server.get("/users/:id/avatar.png", |req, res| {
+            res.set_header("X-User", req.param("id").unwrap())
+                .json(r#"{"avatar":true}"#);
+        });
+        server.post("/users", |_req, res| {
+            res.status(201).send("created");
+        });
+
+        server.assert_head_get_consistency();
+    }
+
+    // TEST - a HEAD route that disagrees with its GET route fails the check
+    #[test]
+    #[should_panic(expected = "HEAD /report answered with different headers than GET")]
+    fn test_assert_head_get_consistency_mismatch() {
+        let mut server = Server::new("0");
+        server.get("/report", |_req, res| {
+            res.send("report");
+        });
+        server.head("/report", |_req, res| {
+            res.status(204).end();
+        });
+
+        server.assert_head_get_consistency();
+    }
 }
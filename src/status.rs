@@ -17,8 +17,6 @@
 /// // Get reason phrase
 /// assert_eq!(HttpStatus::reason(404), "Not Found");
 /// ```
-
-/// Represents Standard HTTP status Codes
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum HttpStatus {
     // 1xx Informational
@@ -194,6 +192,43 @@ impl HttpStatus {
     }
 }
 
+/// The class of a status code, given by its first digit.
+///
+/// # Example
+/// ```
+/// use rxpress::StatusClass;
+///
+/// assert_eq!(StatusClass::of(404), Some(StatusClass::ClientError));
+/// assert_eq!(StatusClass::of(99), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatusClass {
+    /// 1xx
+    Informational,
+    /// 2xx
+    Success,
+    /// 3xx
+    Redirection,
+    /// 4xx
+    ClientError,
+    /// 5xx
+    ServerError,
+}
+
+impl StatusClass {
+    /// Returns the class of `code`, or `None` outside `100..=599`.
+    pub fn of(code: u16) -> Option<StatusClass> {
+        match code {
+            100..=199 => Some(StatusClass::Informational),
+            200..=299 => Some(StatusClass::Success),
+            300..=399 => Some(StatusClass::Redirection),
+            400..=499 => Some(StatusClass::ClientError),
+            500..=599 => Some(StatusClass::ServerError),
+            _ => None,
+        }
+    }
+}
+
 /// Internal enum to support flexible `Response::status` usage.
 pub enum StatusArg<'a> {
     /// Standard enum variant
@@ -243,6 +278,17 @@ mod tests {
         assert_ne!(HttpStatus::reason(418), "I'm a teapot");
     }
 
+    // TEST - status classes
+    #[test]
+    fn test_status_class_of() {
+        assert_eq!(StatusClass::of(101), Some(StatusClass::Informational));
+        assert_eq!(StatusClass::of(204), Some(StatusClass::Success));
+        assert_eq!(StatusClass::of(308), Some(StatusClass::Redirection));
+        assert_eq!(StatusClass::of(418), Some(StatusClass::ClientError));
+        assert_eq!(StatusClass::of(599), Some(StatusClass::ServerError));
+        assert_eq!(StatusClass::of(600), None);
+    }
+
     // TEST - status args
     #[test]
     fn test_status_arg_from() {
@@ -0,0 +1,350 @@
+//! # Test Util Module
+//!
+//! A [`TestClient`] for end-to-end tests: it runs a [`Server`] on an ephemeral
+//! loopback port in a background thread and speaks real HTTP/1.1 to it.
+//!
+//! Enabled with the `test-util` feature. Each call opens its own connection
+//! (sent with `Connection: close`), while [`TestClient::connect`] keeps one
+//! open to exercise keep-alive. TLS is not supported, as the server only speaks
+//! plain HTTP.
+//!
+//! ## Example
+//! ```
+//! # #[cfg(feature = "test-util")]
+//! # {
+//! use rxpress::Server;
+//! use rxpress::test_util::TestClient;
+//!
+//! let mut app = Server::new("0");
+//! app.get("/", |_req, res| {
+//!     res.send("Hello!");
+//! });
+//!
+//! let client = TestClient::new(app);
+//! let res = client.get("/");
+//! assert_eq!(res.status, 200);
+//! assert_eq!(res.body, "Hello!");
+//! # }
+//! ```
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+
+use crate::server::Server;
+
+/// Drives a [`Server`] running in a background thread over loopback.
+///
+/// The server thread is detached and lives until the test process exits.
+pub struct TestClient {
+    address: SocketAddr,
+}
+
+/// A keep-alive connection opened with [`TestClient::connect`]; every request
+/// sent on it reuses the same socket.
+pub struct TestConnection {
+    address: SocketAddr,
+    stream: TcpStream,
+    reader: BufReader<TcpStream>,
+}
+
+/// A response read back by [`TestClient`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestResponse {
+    /// Status code from the status line
+    pub status: u16,
+    /// Header fields in the order they were sent
+    pub headers: Vec<(String, String)>,
+    /// Body, decoded lossily as UTF-8
+    pub body: String,
+}
+
+impl TestClient {
+    /// Starts `server` on an ephemeral `127.0.0.1` port, ignoring its configured
+    /// address and extra ports.
+    pub fn new(server: Server) -> TestClient {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind port");
+        let address = listener.local_addr().expect("Failed to read local address");
+
+        thread::spawn(move || server.serve_listeners(&[listener]));
+
+        TestClient { address }
+    }
+
+    /// Returns the address the server is listening on.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Sends a `GET` request for `path`.
+    pub fn get(&self, path: &str) -> TestResponse {
+        self.request("GET", path, &[], "")
+    }
+
+    /// Sends a `POST` request for `path` with `body`.
+    pub fn post(&self, path: &str, body: &str) -> TestResponse {
+        self.request("POST", path, &[], body)
+    }
+
+    /// Sends a request with any method, extra headers, and body.
+    ///
+    /// `Host`, `Content-Length`, and `Connection: close` are added automatically.
+    ///
+    /// # Panics
+    /// Panics if the connection fails or the reply is not a valid HTTP response.
+    pub fn request(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &[(&str, &str)],
+        body: &str,
+    ) -> TestResponse {
+        let mut headers = headers.to_vec();
+        headers.push(("Connection", "close"));
+        let mut stream = TcpStream::connect(self.address).expect("Failed to connect");
+        stream
+            .write_all(raw_request(self.address, method, path, &headers, body).as_bytes())
+            .expect("Failed to send request");
+
+        TestResponse::read(&mut BufReader::new(stream), method)
+    }
+
+    /// Opens a connection that stays alive across requests.
+    ///
+    /// # Panics
+    /// Panics if the connection fails.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "test-util")]
+    /// # {
+    /// use rxpress::Server;
+    /// use rxpress::test_util::TestClient;
+    ///
+    /// let mut app = Server::new("0");
+    /// app.get("/", |_req, res| res.send("again"));
+    ///
+    /// let client = TestClient::new(app);
+    /// let mut conn = client.connect();
+    /// assert_eq!(conn.get("/").body, "again");
+    /// assert_eq!(conn.get("/").header("connection"), Some("keep-alive"));
+    /// # }
+    /// ```
+    pub fn connect(&self) -> TestConnection {
+        let stream = TcpStream::connect(self.address).expect("Failed to connect");
+        let reader = BufReader::new(stream.try_clone().expect("Failed to clone stream"));
+        TestConnection {
+            address: self.address,
+            stream,
+            reader,
+        }
+    }
+}
+
+impl TestConnection {
+    /// Sends a `GET` request for `path`.
+    pub fn get(&mut self, path: &str) -> TestResponse {
+        self.request("GET", path, &[], "")
+    }
+
+    /// Sends a `POST` request for `path` with `body`.
+    pub fn post(&mut self, path: &str, body: &str) -> TestResponse {
+        self.request("POST", path, &[], body)
+    }
+
+    /// Sends a request and reads exactly one response, leaving the connection
+    /// open for the next one.
+    ///
+    /// `Host` and `Content-Length` are added automatically.
+    ///
+    /// # Panics
+    /// Panics if the connection fails or the reply is not a valid HTTP response.
+    pub fn request(
+        &mut self,
+        method: &str,
+        path: &str,
+        headers: &[(&str, &str)],
+        body: &str,
+    ) -> TestResponse {
+        self.send_raw(&raw_request(self.address, method, path, headers, body));
+        TestResponse::read(&mut self.reader, method)
+    }
+
+    /// Writes `raw` to the connection as is, e.g. to test malformed requests.
+    /// Read the answer with [`TestConnection::read_response`].
+    pub fn send_raw(&mut self, raw: &str) {
+        self.stream
+            .write_all(raw.as_bytes())
+            .expect("Failed to send request");
+    }
+
+    /// Reads the next response on the connection (`method` tells whether a
+    /// body follows, as for `HEAD`).
+    pub fn read_response(&mut self, method: &str) -> TestResponse {
+        TestResponse::read(&mut self.reader, method)
+    }
+}
+
+impl TestResponse {
+    /// Reads a header value (case-insensitive).
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /*---- Private Functions ----*/
+    /// Reads one response: status line, headers, and a body framed by
+    /// `Content-Length` or chunked encoding (read to EOF otherwise).
+    fn read<R: BufRead>(reader: &mut R, method: &str) -> TestResponse {
+        let status = read_line(reader)
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse().ok())
+            .expect("Response has no valid status line");
+        let mut headers = Vec::new();
+        loop {
+            let line = read_line(reader);
+            if line.is_empty() {
+                break;
+            }
+            if let Some((k, v)) = line.split_once(':') {
+                headers.push((k.trim().to_string(), v.trim().to_string()));
+            }
+        }
+        let mut res = TestResponse {
+            status,
+            headers,
+            body: String::new(),
+        };
+
+        // no body follows a HEAD request, a 1xx, a 204, or a 304
+        if method.eq_ignore_ascii_case("HEAD") || matches!(status, 100..=199 | 204 | 304) {
+            return res;
+        }
+        let mut body = Vec::new();
+        if res
+            .header("Transfer-Encoding")
+            .is_some_and(|te| te.eq_ignore_ascii_case("chunked"))
+        {
+            loop {
+                let line = read_line(reader);
+                let size = line.split(';').next().unwrap_or("").trim();
+                let size = usize::from_str_radix(size, 16).expect("Invalid chunk size");
+                if size == 0 {
+                    break;
+                }
+                let start = body.len();
+                body.resize(start + size, 0);
+                reader
+                    .read_exact(&mut body[start..])
+                    .expect("Failed to read chunk");
+                read_line(reader);
+            }
+            // skip trailers up to the final empty line
+            while !read_line(reader).is_empty() {}
+        } else if let Some(len) = res.header("Content-Length").and_then(|v| v.parse().ok()) {
+            body.resize(len, 0);
+            reader
+                .read_exact(&mut body)
+                .expect("Failed to read response body");
+        } else {
+            reader
+                .read_to_end(&mut body)
+                .expect("Failed to read response");
+        }
+        res.body = String::from_utf8_lossy(&body).to_string();
+        res
+    }
+}
+
+/// Formats a request with `Host` and, for a non-empty body, `Content-Length`.
+fn raw_request(
+    address: SocketAddr,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> String {
+    let mut raw = format!("{} {} HTTP/1.1\r\nHost: {}\r\n", method, path, address);
+    for (key, value) in headers {
+        raw.push_str(&format!("{}: {}\r\n", key, value));
+    }
+    if !body.is_empty() {
+        raw.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    raw.push_str("\r\n");
+    raw.push_str(body);
+    raw
+}
+
+/// Reads one line without its CRLF, panicking if the connection ends first.
+fn read_line<R: BufRead>(reader: &mut R) -> String {
+    let mut line = String::new();
+    let read = reader
+        .read_line(&mut line)
+        .expect("Failed to read response");
+    assert!(read > 0, "Connection closed before a complete response");
+    line.trim_end_matches(['\r', '\n']).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // TEST - requests go over a real socket and come back parsed
+    #[test]
+    fn test_client_get_and_post() {
+        let mut server = Server::new("0");
+        server.get("/hello", |_req, res| {
+            res.set_header("X-Test", "1").send("Hello!");
+        });
+        server.post("/echo", |req, res| {
+            res.status(201).send(&req.body);
+        });
+
+        let client = TestClient::new(server);
+        let res = client.get("/hello");
+        assert_eq!(res.status, 200);
+        assert_eq!(res.header("x-test"), Some("1"));
+        assert_eq!(res.body, "Hello!");
+
+        let res = client.post("/echo", "ping");
+        assert_eq!(res.status, 201);
+        assert_eq!(res.body, "ping");
+
+        assert_eq!(client.get("/missing").status, 404);
+    }
+
+    // TEST - several requests share one kept-alive connection
+    #[test]
+    fn test_connection_keep_alive() {
+        let mut server = Server::new("0");
+        server.get("/peer", |req, res| {
+            res.send(&req.peer_addr().map(|a| a.to_string()).unwrap_or_default());
+        });
+        server.get("/numbers", |_req, res| {
+            let mut array = res.json_stream();
+            for n in 0..3 {
+                array.push(&crate::Json::Number(n as f64)).unwrap();
+            }
+        });
+
+        let client = TestClient::new(server);
+        let mut conn = client.connect();
+        let first = conn.get("/peer");
+        assert_eq!(first.header("connection"), Some("keep-alive"));
+
+        // the chunk framing is decoded, and the next response still lines up
+        let streamed = conn.get("/numbers");
+        assert_eq!(streamed.header("transfer-encoding"), Some("chunked"));
+        assert_eq!(streamed.body, "[0,1,2]");
+        assert_eq!(conn.request("HEAD", "/peer", &[], "").body, "");
+        assert_eq!(conn.get("/peer").body, first.body);
+
+        // the pool may have a single worker, busy until the kept-alive one closes
+        drop(conn);
+        assert_eq!(client.get("/numbers").body, "[0,1,2]");
+    }
+}